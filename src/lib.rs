@@ -0,0 +1,2402 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc};
+use polars::datatypes::DataType as PolarsDataType;
+use polars::prelude::*;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use rayon::prelude::*;
+use regex::Regex;
+use reqwest::blocking::{Client, ClientBuilder};
+use reqwest::Proxy;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::{BufRead, Read};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use urlencoding::encode;
+use ::zip::read::ZipFile;
+use ::zip::ZipArchive;
+
+mod pipeline;
+mod records;
+mod server;
+pub use pipeline::{DownloadPlan, DownloadedFile};
+pub use records::{get, AggTrade, BinanceRecord, DataKind, Kline, Trade};
+pub use server::serve;
+
+const BASE_URL: &str = "https://data.binance.vision";
+pub(crate) const CLEAN_ROOT: &str = "parquet.binance.vision";
+const RAW_ROOT: &str = "raw.binance.vision";
+
+/// Where `download_to_file` should stage the archive for `url` before it is
+/// handed off to `verify_checksum`/`clean_zip_bytes`: a per-pattern directory
+/// holding the archive's own file name, which carries the symbol, so
+/// concurrent symbols never collide on the same `.part` file.
+fn raw_download_path(pattern: &str, url: &str) -> Result<PathBuf> {
+    let file_name = extract_zip_name(url).context("could not derive archive file name from url")?;
+    let dir = PathBuf::from(RAW_ROOT).join(pattern);
+    fs::create_dir_all(&dir)?;
+    Ok(dir.join(file_name))
+}
+
+/// One Binance market-data archive family, with its canonical column layout.
+///
+/// Binance publishes many archive types under `data.binance.vision`, each with
+/// a different CSV column set. `BinanceDataType` is the schema registry that tells
+/// `clean_zip_bytes` which names to assign and which Polars dtype each column
+/// should be cast to, instead of hardcoding the kline layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinanceDataType {
+    Klines,
+    AggTrades,
+    Trades,
+    BookTicker,
+    BookDepth,
+    IndexPriceKlines,
+    MarkPriceKlines,
+    /// USDⓈ-M futures premium index klines (the basis used to compute funding).
+    PremiumIndexKlines,
+    FundingRate,
+    LiquidationSnapshot,
+}
+
+impl BinanceDataType {
+    /// Canonical column names, in CSV order, for this archive type.
+    pub fn columns(self) -> &'static [&'static str] {
+        match self {
+            BinanceDataType::Klines
+            | BinanceDataType::IndexPriceKlines
+            | BinanceDataType::MarkPriceKlines
+            | BinanceDataType::PremiumIndexKlines => &[
+                "open_time",
+                "open",
+                "high",
+                "low",
+                "close",
+                "volume",
+                "close_time",
+                "quote_asset_volume",
+                "number_of_trades",
+                "taker_buy_base_asset_volume",
+                "taker_buy_quote_asset_volume",
+                "ignore",
+            ],
+            BinanceDataType::AggTrades => &[
+                "agg_trade_id",
+                "price",
+                "quantity",
+                "first_trade_id",
+                "last_trade_id",
+                "transact_time",
+                "is_buyer_maker",
+            ],
+            BinanceDataType::Trades => &[
+                "trade_id",
+                "price",
+                "quantity",
+                "quote_quantity",
+                "time",
+                "is_buyer_maker",
+                "is_best_match",
+            ],
+            BinanceDataType::BookTicker => &[
+                "update_id",
+                "best_bid_price",
+                "best_bid_qty",
+                "best_ask_price",
+                "best_ask_qty",
+                "transaction_time",
+                "event_time",
+            ],
+            BinanceDataType::BookDepth => &[
+                "timestamp",
+                "first_update_id",
+                "last_update_id",
+                "side",
+                "price",
+                "quantity",
+            ],
+            BinanceDataType::FundingRate => &["calc_time", "funding_interval_hours", "last_funding_rate"],
+            BinanceDataType::LiquidationSnapshot => &[
+                "symbol",
+                "side",
+                "order_type",
+                "time_in_force",
+                "original_quantity",
+                "price",
+                "average_price",
+                "order_status",
+                "last_fill_quantity",
+                "accumulated_fill_quantity",
+                "trade_time",
+            ],
+        }
+    }
+
+    /// Polars dtype each column in [`BinanceDataType::columns`] should be cast to.
+    pub fn dtypes(self) -> &'static [PolarsDataType] {
+        match self {
+            BinanceDataType::Klines
+            | BinanceDataType::IndexPriceKlines
+            | BinanceDataType::MarkPriceKlines
+            | BinanceDataType::PremiumIndexKlines => &[
+                PolarsDataType::Int64,
+                PolarsDataType::Float64,
+                PolarsDataType::Float64,
+                PolarsDataType::Float64,
+                PolarsDataType::Float64,
+                PolarsDataType::Float64,
+                PolarsDataType::Int64,
+                PolarsDataType::Float64,
+                PolarsDataType::Int64,
+                PolarsDataType::Float64,
+                PolarsDataType::Float64,
+                PolarsDataType::Int64,
+            ],
+            BinanceDataType::AggTrades => &[
+                PolarsDataType::Int64,
+                PolarsDataType::Float64,
+                PolarsDataType::Float64,
+                PolarsDataType::Int64,
+                PolarsDataType::Int64,
+                PolarsDataType::Int64,
+                PolarsDataType::Boolean,
+            ],
+            BinanceDataType::Trades => &[
+                PolarsDataType::Int64,
+                PolarsDataType::Float64,
+                PolarsDataType::Float64,
+                PolarsDataType::Float64,
+                PolarsDataType::Int64,
+                PolarsDataType::Boolean,
+                PolarsDataType::Boolean,
+            ],
+            BinanceDataType::BookTicker => &[
+                PolarsDataType::Int64,
+                PolarsDataType::Float64,
+                PolarsDataType::Float64,
+                PolarsDataType::Float64,
+                PolarsDataType::Float64,
+                PolarsDataType::Int64,
+                PolarsDataType::Int64,
+            ],
+            BinanceDataType::BookDepth => &[
+                PolarsDataType::Int64,
+                PolarsDataType::Int64,
+                PolarsDataType::Int64,
+                PolarsDataType::Utf8,
+                PolarsDataType::Float64,
+                PolarsDataType::Float64,
+            ],
+            BinanceDataType::FundingRate => &[PolarsDataType::Int64, PolarsDataType::Float64, PolarsDataType::Float64],
+            BinanceDataType::LiquidationSnapshot => &[
+                PolarsDataType::Utf8,
+                PolarsDataType::Utf8,
+                PolarsDataType::Utf8,
+                PolarsDataType::Utf8,
+                PolarsDataType::Float64,
+                PolarsDataType::Float64,
+                PolarsDataType::Float64,
+                PolarsDataType::Utf8,
+                PolarsDataType::Float64,
+                PolarsDataType::Float64,
+                PolarsDataType::Int64,
+            ],
+        }
+    }
+
+    /// Name of this type's timestamp column in [`BinanceDataType::columns`],
+    /// for windowed reads like [`query_range`] -- only the Klines family
+    /// calls it `open_time`, every other archive type names it differently.
+    pub fn time_column(self) -> &'static str {
+        match self {
+            BinanceDataType::Klines
+            | BinanceDataType::IndexPriceKlines
+            | BinanceDataType::MarkPriceKlines
+            | BinanceDataType::PremiumIndexKlines => "open_time",
+            BinanceDataType::AggTrades => "transact_time",
+            BinanceDataType::Trades => "time",
+            BinanceDataType::BookTicker => "transaction_time",
+            BinanceDataType::BookDepth => "timestamp",
+            BinanceDataType::FundingRate => "calc_time",
+            BinanceDataType::LiquidationSnapshot => "trade_time",
+        }
+    }
+
+    /// Parse a Binance archive-type token such as `"klines"` or `"aggTrades"`.
+    pub fn from_token(token: &str) -> Option<Self> {
+        match token {
+            "klines" => Some(BinanceDataType::Klines),
+            "aggTrades" => Some(BinanceDataType::AggTrades),
+            "trades" => Some(BinanceDataType::Trades),
+            "bookTicker" => Some(BinanceDataType::BookTicker),
+            "bookDepth" => Some(BinanceDataType::BookDepth),
+            "indexPriceKlines" => Some(BinanceDataType::IndexPriceKlines),
+            "markPriceKlines" => Some(BinanceDataType::MarkPriceKlines),
+            "premiumIndexKlines" => Some(BinanceDataType::PremiumIndexKlines),
+            "fundingRate" => Some(BinanceDataType::FundingRate),
+            "liquidationSnapshot" => Some(BinanceDataType::LiquidationSnapshot),
+            _ => None,
+        }
+    }
+
+    /// The archive-type path segment Binance publishes this data type under,
+    /// e.g. `"klines"` -- the inverse of [`BinanceDataType::from_token`].
+    fn token(self) -> &'static str {
+        match self {
+            BinanceDataType::Klines => "klines",
+            BinanceDataType::AggTrades => "aggTrades",
+            BinanceDataType::Trades => "trades",
+            BinanceDataType::BookTicker => "bookTicker",
+            BinanceDataType::BookDepth => "bookDepth",
+            BinanceDataType::IndexPriceKlines => "indexPriceKlines",
+            BinanceDataType::MarkPriceKlines => "markPriceKlines",
+            BinanceDataType::PremiumIndexKlines => "premiumIndexKlines",
+            BinanceDataType::FundingRate => "fundingRate",
+            BinanceDataType::LiquidationSnapshot => "liquidationSnapshot",
+        }
+    }
+}
+
+/// Which Binance product family a pattern belongs to. Parameterizes the
+/// `data/...` path prefix that [`build_pattern`] composes, so the same
+/// `SYMBOL`-templated pattern strings [`BinanceVisionClient::discover`]
+/// already understands also reach USDⓈ-M and COIN-M futures, not just spot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarketType {
+    Spot,
+    UsdMFutures,
+    CoinMFutures,
+}
+
+impl MarketType {
+    fn path_prefix(self) -> &'static str {
+        match self {
+            MarketType::Spot => "data/spot",
+            MarketType::UsdMFutures => "data/futures/um",
+            MarketType::CoinMFutures => "data/futures/cm",
+        }
+    }
+}
+
+/// Compose a `SYMBOL`-templated daily archive pattern for `market`/`data_type`,
+/// e.g. `build_pattern(MarketType::UsdMFutures, BinanceDataType::FundingRate, None)`
+/// is `"data/futures/um/daily/fundingRate/SYMBOL/"`, or with `Some("1m")` for a
+/// kline-shaped type, `"data/spot/daily/klines/SYMBOL/1m/"`.
+pub fn build_pattern(market: MarketType, data_type: BinanceDataType, interval: Option<&str>) -> String {
+    match interval {
+        Some(interval) => format!("{}/daily/{}/SYMBOL/{}/", market.path_prefix(), data_type.token(), interval),
+        None => format!("{}/daily/{}/SYMBOL/", market.path_prefix(), data_type.token()),
+    }
+}
+
+fn wildcard_match(text: &str, pattern: &str) -> bool {
+    let escaped = regex::escape(pattern);
+    let regex_pattern = format!(
+        "^{}$",
+        escaped.replace(r"\*", ".*").replace(r"\?", ".")
+    );
+    Regex::new(&regex_pattern)
+        .map(|re| re.is_match(text))
+        .unwrap_or(false)
+}
+
+fn get_bucket_url_with_base(client: &Client, base_url: &str, prefix: &str) -> Result<String> {
+    let listing_url = format!("{}/?prefix={}", base_url, encode(prefix));
+    let html = client.get(listing_url).send()?.text()?;
+    let re = Regex::new(r"var BUCKET_URL = '(.*?)';")?;
+    let caps = re
+        .captures(&html)
+        .context("BUCKET_URL not found in index page")?;
+    Ok(caps.get(1).context("BUCKET_URL missing")?.as_str().to_string())
+}
+
+fn parse_listing(prefix: &str, xml_content: &str) -> Result<(Vec<(String, bool)>, bool, Option<String>)> {
+    let mut entries: Vec<(String, bool)> = Vec::new();
+    let mut reader = Reader::from_str(xml_content);
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+    let mut current_tag = String::new();
+    let mut is_truncated = false;
+    let mut next_marker: Option<String> = None;
+    let mut last_key: Option<String> = None;
+    let mut in_common_prefix = false;
+    while let Ok(event) = reader.read_event_into(&mut buf) {
+        match event {
+            Event::Start(e) => {
+                current_tag = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                if current_tag.ends_with("CommonPrefixes") {
+                    in_common_prefix = true;
+                }
+            }
+            Event::End(e) => {
+                let tag = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                if tag.ends_with("CommonPrefixes") {
+                    in_common_prefix = false;
+                }
+            }
+            Event::Text(e) => {
+                let text = e.unescape()?.to_string();
+                if current_tag.ends_with("Prefix") && in_common_prefix {
+                    let name = text.trim_start_matches(prefix).trim_matches('/');
+                    if !name.is_empty() {
+                        entries.push((name.to_string(), true));
+                    }
+                } else if current_tag.ends_with("Key") {
+                    last_key = Some(text.clone());
+                    if text.ends_with(".zip") {
+                        let name = text.trim_start_matches(prefix);
+                        if !name.is_empty() {
+                            entries.push((name.to_string(), false));
+                        }
+                    }
+                } else if current_tag.ends_with("IsTruncated") {
+                    is_truncated = text.to_lowercase() == "true";
+                } else if current_tag.ends_with("NextMarker") {
+                    next_marker = Some(text);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    entries.sort_by_key(|entry| (!entry.1, entry.0.clone()));
+    let continuation = if is_truncated {
+        next_marker.or(last_key)
+    } else {
+        None
+    };
+    Ok((entries, is_truncated, continuation))
+}
+
+/// ListObjectsV2-style query, modeled on garage's `parse_list_objects_query`/
+/// `handle_list`: unlike [`list_prefix_with_base`]'s V1 pagination, a single
+/// [`BinanceVisionClient::list_prefix_page`] call is bounded by `max_keys`
+/// and hands back a continuation token instead of eagerly draining the
+/// whole prefix -- useful for paging through enormous listings like
+/// `data/spot/daily/klines/` incrementally.
+#[derive(Debug, Clone)]
+pub struct ListQuery {
+    pub prefix: String,
+    /// `Some("/")` groups keys under common prefixes (the default S3 uses to
+    /// emulate directories); `None` lists every key under `prefix` flat.
+    pub delimiter: Option<String>,
+    /// Upper bound on the number of entries a single page returns. `0` means
+    /// let the server pick its own default page size.
+    pub max_keys: usize,
+    pub start_after: Option<String>,
+    /// Pass back the `next_continuation_token` from a prior [`ListPage`] to
+    /// resume after it; `None` starts from the beginning.
+    pub continuation_token: Option<String>,
+}
+
+impl Default for ListQuery {
+    fn default() -> Self {
+        Self {
+            prefix: String::new(),
+            delimiter: Some("/".to_string()),
+            max_keys: 1000,
+            start_after: None,
+            continuation_token: None,
+        }
+    }
+}
+
+/// One page of a [`ListQuery`] result.
+#[derive(Debug, Clone, Default)]
+pub struct ListPage {
+    pub entries: Vec<(String, bool)>,
+    pub key_count: usize,
+    pub is_truncated: bool,
+    /// Present when `is_truncated` is true; feed back into the next
+    /// [`ListQuery::continuation_token`] to resume.
+    pub next_continuation_token: Option<String>,
+}
+
+fn parse_listing_v2(prefix: &str, xml_content: &str) -> Result<ListPage> {
+    let mut entries: Vec<(String, bool)> = Vec::new();
+    let mut reader = Reader::from_str(xml_content);
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+    let mut current_tag = String::new();
+    let mut is_truncated = false;
+    let mut key_count = 0usize;
+    let mut next_continuation_token: Option<String> = None;
+    let mut in_common_prefix = false;
+    while let Ok(event) = reader.read_event_into(&mut buf) {
+        match event {
+            Event::Start(e) => {
+                current_tag = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                if current_tag.ends_with("CommonPrefixes") {
+                    in_common_prefix = true;
+                }
+            }
+            Event::End(e) => {
+                let tag = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                if tag.ends_with("CommonPrefixes") {
+                    in_common_prefix = false;
+                }
+            }
+            Event::Text(e) => {
+                let text = e.unescape()?.to_string();
+                if current_tag.ends_with("Prefix") && in_common_prefix {
+                    let name = text.trim_start_matches(prefix).trim_matches('/');
+                    if !name.is_empty() {
+                        entries.push((name.to_string(), true));
+                    }
+                } else if current_tag.ends_with("Key") {
+                    if text.ends_with(".zip") {
+                        let name = text.trim_start_matches(prefix);
+                        if !name.is_empty() {
+                            entries.push((name.to_string(), false));
+                        }
+                    }
+                } else if current_tag.ends_with("IsTruncated") {
+                    is_truncated = text.to_lowercase() == "true";
+                } else if current_tag.ends_with("KeyCount") {
+                    key_count = text.parse().unwrap_or(0);
+                } else if current_tag.ends_with("NextContinuationToken") {
+                    next_continuation_token = Some(text);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    entries.sort_by_key(|entry| (!entry.1, entry.0.clone()));
+    Ok(ListPage {
+        entries,
+        key_count,
+        is_truncated,
+        next_continuation_token: if is_truncated { next_continuation_token } else { None },
+    })
+}
+
+fn list_prefix_with_base(client: &Client, base_url: &str, prefix: &str) -> Result<Vec<(String, bool)>> {
+    let bucket_url = get_bucket_url_with_base(client, base_url, prefix)?;
+    let mut entries: Vec<(String, bool)> = Vec::new();
+    let mut continuation: Option<String> = None;
+
+    loop {
+        let mut params = format!("delimiter=/&prefix={}", encode(prefix));
+        if let Some(marker) = &continuation {
+            params.push_str(&format!("&marker={}", encode(marker)));
+        }
+        let request_url = format!("{}?{}", bucket_url, params);
+        let xml_content = client.get(request_url).send()?.text()?;
+        let (mut batch, is_truncated, next_marker) = parse_listing(prefix, &xml_content)?;
+        entries.append(&mut batch);
+
+        if !is_truncated {
+            break;
+        }
+        continuation = next_marker;
+        if continuation.is_none() {
+            break;
+        }
+    }
+
+    entries.sort_by_key(|entry| (!entry.1, entry.0.clone()));
+    Ok(entries)
+}
+
+fn encoded_url(path: &str, file_name: &str) -> String {
+    let encoded_path = encode(path).replace("%2F", "/");
+    let encoded_name = encode(file_name);
+    format!("{}/{}/{}", BASE_URL, encoded_path.trim_end_matches('/'), encoded_name)
+}
+
+/// Download `url` fully into memory, resuming via `Range: bytes=<len>-` and
+/// retrying with exponential backoff (see [`retry_config`]) on a dropped
+/// connection or 5xx status (other non-2xx statuses fail at once). Falls back to restarting from scratch if a
+/// resume attempt doesn't come back `206 Partial Content`, since not every
+/// server honors ranges.
+///
+/// In-memory counterpart to [`download_to_file`], which streams to disk instead.
+fn download_one(client: &Client, url: &str, chunk_bytes: usize) -> Result<Vec<u8>> {
+    let (max_retries, base_delay) = retry_config();
+    let mut output: Vec<u8> = Vec::new();
+    let mut supports_range = true;
+
+    for attempt in 0..=max_retries {
+        if attempt > 0 {
+            std::thread::sleep(base_delay * 2u32.pow((attempt - 1) as u32));
+        }
+        let resuming = supports_range && !output.is_empty();
+        let mut request = client.get(url);
+        if resuming {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", output.len()));
+        }
+
+        let response = match request.send() {
+            Ok(response) => response,
+            Err(err) if attempt < max_retries => {
+                println!("download attempt {} for {} failed: {}, retrying", attempt + 1, url, err);
+                continue;
+            }
+            Err(err) => return Err(err).context("download failed after exhausting retries"),
+        };
+
+        let status = response.status();
+        if resuming && status != reqwest::StatusCode::PARTIAL_CONTENT {
+            supports_range = false;
+            output.clear();
+        }
+        if !status.is_success() {
+            if status.is_server_error() && attempt < max_retries {
+                println!("download attempt {} for {} returned {}, retrying", attempt + 1, url, status);
+                continue;
+            }
+            anyhow::bail!("download of {} failed with status {}", url, status);
+        }
+
+        let mut response = response;
+        let mut buffer = vec![0u8; chunk_bytes];
+        let read_result = loop {
+            match response.read(&mut buffer) {
+                Ok(0) => break Ok(()),
+                Ok(read) => output.extend_from_slice(&buffer[..read]),
+                Err(err) => break Err(err),
+            }
+        };
+
+        match read_result {
+            Ok(()) => return Ok(output),
+            Err(err) if attempt < max_retries => {
+                println!("download attempt {} for {} dropped mid-transfer: {}, retrying", attempt + 1, url, err);
+                continue;
+            }
+            Err(err) => return Err(err).context("download interrupted after exhausting retries"),
+        }
+    }
+    anyhow::bail!("exhausted retries downloading {}", url)
+}
+
+/// Like [`download_one`], but also fetches `<url>.CHECKSUM` and fails with
+/// both digests if the downloaded bytes don't match. Missing sidecars are
+/// treated as nothing to verify, since not every prefix publishes one.
+///
+/// In-memory counterpart to [`verify_checksum`]; unlike that function, which
+/// returns the mismatch for [`ChecksumPolicy`] to act on, this always
+/// hard-fails on mismatch, with no warn/skip modes.
+fn download_verified(client: &Client, url: &str, chunk_bytes: usize) -> Result<Vec<u8>> {
+    let bytes = download_one(client, url, chunk_bytes)?;
+    let checksum_url = format!("{}.CHECKSUM", url);
+    let response = client.get(&checksum_url).send()?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(bytes);
+    }
+    let body = response.error_for_status()?.text()?;
+    let expected = parse_checksum_body(&body).context("empty .CHECKSUM body")?;
+    let actual = sha256_hex(&bytes);
+    anyhow::ensure!(
+        actual == expected,
+        "checksum mismatch for {}: expected {}, got {}",
+        url,
+        expected,
+        actual
+    );
+    Ok(bytes)
+}
+
+/// Root of the on-disk download cache, honoring `XDG_CACHE_HOME` and falling
+/// back to `$HOME/.cache` as most CLI cache layouts do.
+fn cache_root() -> Result<PathBuf> {
+    let base = match env::var("XDG_CACHE_HOME") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var("HOME").context("neither XDG_CACHE_HOME nor HOME is set")?).join(".cache"),
+    };
+    Ok(base.join("binance-historical-data"))
+}
+
+/// Stable 16-char hex digest of `value`, modeled on cargo-fetcher's
+/// `short_hash`: enough bits to make collisions a non-issue for a cache key,
+/// short enough to keep file names readable.
+fn short_hash(value: &str) -> String {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    value.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Download `url` via [`download_verified`], caching the bytes on disk at
+/// `<cache_root>/<short_hash(url)>-<file_name>` so repeat runs over Binance's
+/// immutable dated archives become local reads instead of re-downloads.
+fn download_cached(client: &Client, url: &str, chunk_bytes: usize) -> Result<Vec<u8>> {
+    let file_name = extract_zip_name(url).context("could not derive archive file name from url")?;
+    let cache_dir = cache_root()?;
+    let cached_path = cache_dir.join(format!("{}-{}", short_hash(url), file_name));
+
+    if let Ok(bytes) = fs::read(&cached_path) {
+        return Ok(bytes);
+    }
+
+    let bytes = download_verified(client, url, chunk_bytes)?;
+    fs::create_dir_all(&cache_dir)?;
+    let temp_path = cache_dir.join(format!("{}-{}.part", short_hash(url), file_name));
+    fs::write(&temp_path, &bytes)?;
+    fs::rename(&temp_path, &cached_path)?;
+    Ok(bytes)
+}
+
+/// Hand `f` the single CSV member of an in-memory `.zip` buffer as a `Read`,
+/// along with its name inside the archive -- reusable as a cache key or
+/// `.CHECKSUM` file name. Errors clearly if the archive holds more than one
+/// member or its lone member isn't a `.csv`, since every Binance data file
+/// packages exactly one. Shared by [`BinanceVisionClient::fetch_csv`] and
+/// `records::parse_archive`, so the single-entry assumption is validated in
+/// one place instead of per caller.
+///
+/// [`BinanceVisionClient::fetch_csv`]: BinanceVisionClient::fetch_csv
+pub(crate) fn with_single_csv_entry<T>(
+    archive_bytes: &[u8],
+    f: impl FnOnce(&str, &mut dyn Read) -> Result<T>,
+) -> Result<T> {
+    let mut archive = ZipArchive::new(std::io::Cursor::new(archive_bytes))?;
+    anyhow::ensure!(archive.len() == 1, "expected a single-entry archive, found {}", archive.len());
+    let mut entry = archive.by_index(0)?;
+    let name = entry.name().to_string();
+    anyhow::ensure!(name.ends_with(".csv"), "archive entry {:?} is not a .csv file", name);
+    f(&name, &mut entry)
+}
+
+/// Max retries after the first attempt (so up to `max_retries + 1` attempts
+/// in all) and exponential-backoff base delay for retryable transfers, via
+/// `BINANCE_MAX_RETRIES` / `BINANCE_RETRY_BASE_MS`.
+fn retry_config() -> (usize, std::time::Duration) {
+    let max_retries: usize = env::var("BINANCE_MAX_RETRIES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(5);
+    let base_delay_ms: u64 = env::var("BINANCE_RETRY_BASE_MS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(500);
+    (max_retries, std::time::Duration::from_millis(base_delay_ms))
+}
+
+/// Stream `url` to `<dest>.part`, resuming from where a prior attempt left
+/// off via `Range: bytes=<len>-` and retrying connection resets / 5xx with
+/// exponential backoff, then atomically rename to `dest` once the file's
+/// byte count matches `Content-Length`. Large monthly archives are written
+/// straight to disk instead of buffered fully in memory.
+fn download_to_file(client: &Client, url: &str, dest: &std::path::Path) -> Result<()> {
+    use std::io::Write;
+    let part_path = PathBuf::from(format!("{}.part", dest.display()));
+    let (max_retries, base_delay) = retry_config();
+
+    for attempt in 0..=max_retries {
+        if attempt > 0 {
+            std::thread::sleep(base_delay * 2u32.pow((attempt - 1) as u32));
+        }
+        let existing_len = fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0);
+        let mut request = client.get(url);
+        if existing_len > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", existing_len));
+        }
+
+        let response = match request.send() {
+            Ok(response) => response,
+            Err(err) if attempt < max_retries => {
+                println!("download attempt {} for {} failed: {}, retrying", attempt + 1, url, err);
+                continue;
+            }
+            Err(err) => return Err(err).context("download failed after exhausting retries"),
+        };
+
+        let status = response.status();
+        if !status.is_success() {
+            if status.is_server_error() && attempt < max_retries {
+                println!("download attempt {} for {} returned {}, retrying", attempt + 1, url, status);
+                continue;
+            }
+            anyhow::bail!("download of {} failed with status {}", url, status);
+        }
+
+        let resumed = status == reqwest::StatusCode::PARTIAL_CONTENT;
+        let content_length = response.content_length();
+        let expected_len = content_length.map(|len| if resumed { existing_len + len } else { len });
+
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(resumed)
+            .truncate(!resumed)
+            .open(&part_path)?;
+        let mut writer = std::io::BufWriter::new(file);
+        let mut response = response;
+        let copy_result = std::io::copy(&mut response, &mut writer).and_then(|_| writer.flush());
+
+        if let Err(err) = copy_result {
+            if attempt < max_retries {
+                println!("download attempt {} for {} dropped mid-transfer: {}, retrying", attempt + 1, url, err);
+                continue;
+            }
+            return Err(err).context("download interrupted after exhausting retries");
+        }
+
+        let final_len = fs::metadata(&part_path)?.len();
+        if let Some(expected) = expected_len {
+            if final_len != expected {
+                if attempt < max_retries {
+                    println!(
+                        "incomplete download for {} ({} of {} bytes), retrying",
+                        url, final_len, expected
+                    );
+                    continue;
+                }
+                anyhow::bail!(
+                    "incomplete download for {} after exhausting retries ({} of {} bytes)",
+                    url,
+                    final_len,
+                    expected
+                );
+            }
+        }
+
+        fs::rename(&part_path, dest)?;
+        return Ok(());
+    }
+    anyhow::bail!("exhausted retries downloading {}", url)
+}
+
+/// Result of checking a downloaded archive against its `.CHECKSUM` sidecar.
+enum ChecksumOutcome {
+    /// The digest matched.
+    Verified,
+    /// Binance doesn't publish a sidecar for this prefix; nothing to check.
+    SidecarMissing,
+    /// The digest didn't match -- the download is corrupt or truncated.
+    Mismatch { expected: String, actual: String },
+}
+
+/// How strictly a [`BinanceVisionClient`] should treat a `.CHECKSUM`
+/// mismatch: ignore it, warn and keep the file, or fail the download.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumPolicy {
+    /// Don't fetch or compare the `.CHECKSUM` sidecar at all.
+    Skip,
+    /// Verify, but only print a warning on mismatch -- the file is kept.
+    WarnOnMismatch,
+    /// Verify and fail the download (deleting the downloaded archive) on mismatch.
+    FailOnMismatch,
+}
+
+/// The default checksum policy, via `BINANCE_VERIFY_CHECKSUM`: `"0"` or
+/// `"false"` means [`ChecksumPolicy::Skip`], `"warn"` means
+/// [`ChecksumPolicy::WarnOnMismatch`], anything else (including unset)
+/// means [`ChecksumPolicy::FailOnMismatch`], since not every Binance prefix
+/// publishes sidecars but reproducible backtests want corrupt archives caught.
+pub fn checksum_policy_from_env() -> ChecksumPolicy {
+    match env::var("BINANCE_VERIFY_CHECKSUM") {
+        Ok(value) if value == "0" || value.eq_ignore_ascii_case("false") => ChecksumPolicy::Skip,
+        Ok(value) if value.eq_ignore_ascii_case("warn") => ChecksumPolicy::WarnOnMismatch,
+        _ => ChecksumPolicy::FailOnMismatch,
+    }
+}
+
+/// Parse `var`'s value as a comma-separated column list for
+/// [`BinanceVisionClientConfig::include_columns`]/`exclude_columns`, or
+/// `None` if the variable is unset or empty.
+pub fn column_list_from_env(var: &str) -> Option<Vec<String>> {
+    let value = env::var(var).ok()?;
+    let columns: Vec<String> = value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect();
+    if columns.is_empty() {
+        None
+    } else {
+        Some(columns)
+    }
+}
+
+/// Lowercase hex SHA256 digest of `bytes`.
+fn sha256_hex(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Parse the expected digest out of a `.CHECKSUM` body, whose format is
+/// `<hex-sha256>  <filename>`.
+fn parse_checksum_body(body: &str) -> Option<String> {
+    let token = body.split_whitespace().next()?;
+    if token.is_empty() {
+        None
+    } else {
+        Some(token.to_lowercase())
+    }
+}
+
+/// SHA256 digest of a file's contents, streamed in chunks so multi-gigabyte
+/// monthly archives don't need to be held in memory to be hashed.
+fn sha256_of_file(path: &std::path::Path) -> Result<String> {
+    use sha2::{Digest, Sha256};
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// Fetch `<url>.CHECKSUM` and compare its digest against the downloaded file at `path`.
+fn verify_checksum(client: &Client, url: &str, path: &std::path::Path) -> Result<ChecksumOutcome> {
+    let checksum_url = format!("{}.CHECKSUM", url);
+    let response = client.get(&checksum_url).send()?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(ChecksumOutcome::SidecarMissing);
+    }
+    let body = response.error_for_status()?.text()?;
+    let expected = parse_checksum_body(&body).context("empty .CHECKSUM body")?;
+    let actual = sha256_of_file(path)?;
+    if actual == expected {
+        Ok(ChecksumOutcome::Verified)
+    } else {
+        Ok(ChecksumOutcome::Mismatch { expected, actual })
+    }
+}
+
+fn has_header(csv_content: &str) -> bool {
+    let first_line = csv_content.lines().next().unwrap_or("");
+    let first_cell = first_line.split(',').next().unwrap_or("");
+    first_cell.parse::<f64>().is_err()
+}
+
+fn normalize_frame(df: DataFrame) -> Result<DataFrame> {
+    let first_column = df
+        .get_column_names()
+        .first()
+        .context("dataframe has no columns")?
+        .to_string();
+    let normalized = df
+        .lazy()
+        .unique(None, UniqueKeepStrategy::First)
+        .sort([first_column.clone()], SortMultipleOptions::default())
+        .collect()?;
+    Ok(normalized)
+}
+
+/// Build the Polars reader schema for `data_type`: its canonical column names
+/// paired with the dtype each should be parsed as, in CSV order.
+fn reader_schema(data_type: BinanceDataType) -> Schema {
+    data_type
+        .columns()
+        .iter()
+        .zip(data_type.dtypes().iter())
+        .map(|(name, dtype)| Field::new(name, dtype.clone()))
+        .collect()
+}
+
+/// Derive the symbol from a Binance archive member's filename, e.g.
+/// `"BTCUSDT-1m-2024-01-01.csv"` -> `"BTCUSDT"` (the leading dash-delimited token).
+fn derive_symbol_from_filename(name: &str) -> Option<String> {
+    let stem = name.rsplit('/').next().unwrap_or(name);
+    let stem = stem.strip_suffix(".csv").unwrap_or(stem);
+    let token = stem.split('-').next()?;
+    if token.is_empty() {
+        None
+    } else {
+        Some(token.to_string())
+    }
+}
+
+/// Derive the partition date from a Binance archive member's filename, e.g.
+/// `"BTCUSDT-1m-2024-01-01.csv"` -> `"2024-01-01"`, or `"BTCUSDT-1m-2024-01.csv"`
+/// -> `"2024-01"` for monthly archives.
+fn derive_date_from_filename(name: &str) -> Option<String> {
+    let stem = name.rsplit('/').next().unwrap_or(name);
+    let re = Regex::new(r"(\d{4}-\d{2}(?:-\d{2})?)").ok()?;
+    re.captures(stem)
+        .and_then(|caps| caps.get(1))
+        .map(|m| m.as_str().to_string())
+}
+
+/// Resolve the set of columns to keep for `data_type`: start from its default
+/// column set, drop `exclude_columns`, then re-add `include_columns` (so an
+/// explicit include always wins over an exclude). Every requested name must
+/// be one of `data_type`'s known columns.
+fn resolve_columns(
+    data_type: BinanceDataType,
+    include_columns: Option<&[String]>,
+    exclude_columns: Option<&[String]>,
+) -> Result<Vec<String>> {
+    let known: HashSet<&str> = data_type.columns().iter().copied().collect();
+    let validate = |name: &str| -> Result<()> {
+        anyhow::ensure!(
+            known.contains(name),
+            "unknown column {:?} for {:?} (known columns: {:?})",
+            name,
+            data_type,
+            data_type.columns()
+        );
+        Ok(())
+    };
+
+    let mut columns: Vec<String> = data_type.columns().iter().map(|c| c.to_string()).collect();
+    if let Some(exclude) = exclude_columns {
+        for name in exclude {
+            validate(name)?;
+        }
+        columns.retain(|c| !exclude.iter().any(|name| name == c));
+    }
+    if let Some(include) = include_columns {
+        for name in include {
+            validate(name)?;
+            if !columns.contains(name) {
+                columns.push(name.clone());
+            }
+        }
+    }
+    Ok(columns)
+}
+
+/// Parse one archive member into a schema-applied `DataFrame` tagged with
+/// `pattern` and the resolved symbol, projected to `include_columns`/
+/// `exclude_columns` (see [`resolve_columns`]). `symbol` pins the member to a
+/// known symbol; pass `None` to derive it from the entry's filename instead.
+/// The partition date is always derived from the entry's filename, since a
+/// multi-file archive can bundle more than one day.
+fn parse_zip_entry(
+    mut zipped: ZipFile,
+    pattern: &str,
+    symbol: Option<&str>,
+    data_type: BinanceDataType,
+    include_columns: Option<&[String]>,
+    exclude_columns: Option<&[String]>,
+) -> Result<(String, String, DataFrame)> {
+    let symbol = match symbol {
+        Some(fixed) => fixed.to_string(),
+        None => derive_symbol_from_filename(zipped.name())
+            .with_context(|| format!("cannot derive symbol from archive entry name {:?}", zipped.name()))?,
+    };
+    let date = derive_date_from_filename(zipped.name())
+        .with_context(|| format!("cannot derive partition date from archive entry name {:?}", zipped.name()))?;
+    let mut buffered = std::io::BufReader::new(&mut zipped);
+    let has_header = {
+        let sample = buffered.fill_buf()?;
+        has_header(&String::from_utf8_lossy(sample))
+    };
+
+    let schema = reader_schema(data_type);
+    let mut df = CsvReadOptions::default()
+        .with_has_header(has_header)
+        .with_schema(Some(Arc::new(schema)))
+        .into_reader_with_file_handle(buffered)
+        .finish()
+        .with_context(|| {
+            format!(
+                "parse csv as {:?} ({} columns expected)",
+                data_type,
+                data_type.columns().len()
+            )
+        })?;
+
+    let kept_columns = resolve_columns(data_type, include_columns, exclude_columns)?;
+    df = df.select(&kept_columns)?;
+
+    df.with_column(Series::new("pattern", vec![pattern; df.height()]))?;
+    df.with_column(Series::new("symbol", vec![symbol.as_str(); df.height()]))?;
+    Ok((symbol, date, df))
+}
+
+/// The Hive-style directory a symbol/date partition is written under:
+/// `<CLEAN_ROOT>/<pattern>/symbol=<symbol>/date=<date>/`.
+fn partition_dir(pattern: &str, symbol: &str, date: &str) -> PathBuf {
+    PathBuf::from(CLEAN_ROOT)
+        .join(pattern)
+        .join(format!("symbol={}", symbol))
+        .join(format!("date={}", date))
+}
+
+/// Write `df`, sorted by its first column (`open_time` for kline-shaped
+/// types), as an immutable date-partition file. Archives are per-day (or
+/// per-month) and never change once published, so this always overwrites the
+/// partition wholesale instead of scanning and re-concatenating existing
+/// data -- re-downloads become idempotent, and ingestion stays append-only
+/// across partitions rather than O(n^2) over a symbol's whole history.
+fn write_partition(pattern: &str, symbol: &str, date: &str, df: DataFrame) -> Result<()> {
+    let mut df = normalize_frame(df)?;
+    let out_dir = partition_dir(pattern, symbol, date);
+    fs::create_dir_all(&out_dir)?;
+    let out_path = out_dir.join("data.parquet");
+    let mut file = fs::File::create(&out_path)?;
+    ParquetWriter::new(&mut file).finish(&mut df)?;
+    Ok(())
+}
+
+/// Slice a symbol's written partitions to rows with `pattern`'s
+/// [`BinanceDataType::time_column`] in `[start, end)`. Scans a glob across
+/// every `date=*` partition directory; since [`write_partition`] writes each
+/// partition sorted by its first column, Parquet row-group statistics let
+/// `scan_parquet`'s predicate pushdown prune whole files/row-groups instead
+/// of scanning every row.
+fn query_range(pattern: &str, symbol: &str, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<DataFrame> {
+    let time_column = data_type_from_pattern(pattern).time_column();
+    let start_ms = start.timestamp_millis();
+    let end_ms = end.timestamp_millis();
+    let glob_path = PathBuf::from(CLEAN_ROOT)
+        .join(pattern)
+        .join(format!("symbol={}", symbol))
+        .join("date=*")
+        .join("data.parquet");
+    LazyFrame::scan_parquet(&glob_path, Default::default())
+        .with_context(|| format!("scan parquet partitions at {}", glob_path.display()))?
+        .filter(col(time_column).gt_eq(lit(start_ms)).and(col(time_column).lt(lit(end_ms))))
+        .collect()
+        .context("collect queried range")
+}
+
+/// Clean a downloaded archive, processing every member rather than just the
+/// first, and write each resulting symbol's rows to its Parquet partition.
+///
+/// `symbol` pins every member to the same symbol (the common case: one
+/// archive per symbol/day); pass `None` to derive it per-member from each
+/// entry's filename instead, for archives that bundle multiple symbols.
+/// `include_columns`/`exclude_columns` narrow the written schema; see
+/// [`resolve_columns`]. Reads `zip_path` via a `File`-backed `ZipArchive`
+/// rather than an in-memory buffer, so a multi-hundred-MB monthly archive
+/// doesn't need to fit in RAM to be cleaned.
+fn clean_zip_bytes(
+    zip_path: &std::path::Path,
+    pattern: &str,
+    symbol: Option<&str>,
+    data_type: BinanceDataType,
+    include_columns: Option<&[String]>,
+    exclude_columns: Option<&[String]>,
+) -> Result<()> {
+    let file = fs::File::open(zip_path).with_context(|| format!("open {}", zip_path.display()))?;
+    let mut archive = ZipArchive::new(file)?;
+    anyhow::ensure!(archive.len() > 0, "archive has no entries");
+
+    let mut by_partition: HashMap<(String, String), Vec<DataFrame>> = HashMap::new();
+    for index in 0..archive.len() {
+        let zipped = archive.by_index(index)?;
+        let (entry_symbol, entry_date, df) = parse_zip_entry(
+            zipped,
+            pattern,
+            symbol,
+            data_type,
+            include_columns,
+            exclude_columns,
+        )?;
+        by_partition
+            .entry((entry_symbol, entry_date))
+            .or_default()
+            .push(df);
+    }
+
+    for ((entry_symbol, entry_date), frames) in by_partition {
+        let mut frames = frames.into_iter();
+        let mut df = frames.next().context("no frames for partition")?;
+        for next in frames {
+            df = df.vstack(&next)?;
+        }
+        write_partition(pattern, &entry_symbol, &entry_date, df)?;
+    }
+
+    Ok(())
+}
+
+fn processed_path(pattern: &str) -> PathBuf {
+    PathBuf::from(CLEAN_ROOT).join(pattern).join("processed.txt")
+}
+
+fn load_processed_urls(path: &PathBuf) -> Result<HashSet<String>> {
+    if !path.exists() {
+        return Ok(HashSet::new());
+    }
+    let contents = fs::read_to_string(path)?;
+    let mut urls = HashSet::new();
+    for line in contents.lines() {
+        for token in line.split_whitespace() {
+            let trimmed = token.trim();
+            if !trimmed.is_empty() {
+                urls.insert(trimmed.to_string());
+            }
+        }
+    }
+    Ok(urls)
+}
+
+fn open_processed_writer(path: &PathBuf) -> Result<Arc<Mutex<fs::File>>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    Ok(Arc::new(Mutex::new(file)))
+}
+
+fn record_processed(writer: &Arc<Mutex<fs::File>>, url: &str) -> Result<()> {
+    use std::io::Write;
+    let mut handle = writer.lock().expect("processed writer lock");
+    if let Some(file_name) = extract_zip_name(url) {
+        writeln!(handle, "{} {}", url, file_name)?;
+    } else {
+        writeln!(handle, "{}", url)?;
+    }
+    Ok(())
+}
+
+fn extract_zip_name(url: &str) -> Option<String> {
+    let trimmed = url.split('?').next().unwrap_or(url);
+    trimmed.rsplit('/').next().map(|name| name.to_string())
+}
+
+fn build_listing_client(proxy_url: Option<&str>) -> Result<Client> {
+    let mut builder = ClientBuilder::new();
+    if let Some(proxy_url) = proxy_url {
+        builder = builder.proxy(Proxy::all(proxy_url)?);
+    }
+    builder.build().context("listing client build")
+}
+
+fn build_urls(
+    listing_client: &Client,
+    pattern: &str,
+    symbol_glob: &str,
+) -> Result<HashMap<String, Vec<String>>> {
+    build_urls_with_base(listing_client, BASE_URL, pattern, symbol_glob)
+}
+
+fn build_urls_with_base(
+    listing_client: &Client,
+    base_url: &str,
+    pattern: &str,
+    symbol_glob: &str,
+) -> Result<HashMap<String, Vec<String>>> {
+    let endpoint = pattern.split("SYMBOL").next().unwrap_or("");
+    let entries = list_prefix_with_base(listing_client, base_url, endpoint)?;
+    let symbols: Vec<String> = entries
+        .iter()
+        .filter(|entry| entry.1 && wildcard_match(&entry.0, symbol_glob))
+        .map(|entry| entry.0.clone())
+        .collect();
+
+    let mut urls: HashMap<String, Vec<String>> = HashMap::new();
+    for symbol in symbols {
+        let path = pattern.replace("SYMBOL", &symbol);
+        let all_zip = list_prefix_with_base(listing_client, base_url, &path)?;
+        for entry in all_zip {
+            if !entry.1 {
+                let url = encoded_url(&path, &entry.0);
+                urls.entry(symbol.clone()).or_default().push(url);
+            }
+        }
+    }
+    Ok(urls)
+}
+
+/// The minimal set of monthly-archive months and daily-archive days needed
+/// to cover `[from, to]` (inclusive) without gaps: a calendar month is
+/// covered by its `monthly/` archive only when the whole month falls inside
+/// the range, otherwise its in-range days fall back to `daily/` archives.
+struct RangePlan {
+    monthly_months: Vec<NaiveDate>,
+    daily_dates: Vec<NaiveDate>,
+}
+
+fn month_last_day(month_start: NaiveDate) -> NaiveDate {
+    month_start
+        .checked_add_months(Months::new(1))
+        .expect("date overflow")
+        - Duration::days(1)
+}
+
+fn plan_range(from: NaiveDate, to: NaiveDate) -> RangePlan {
+    let mut monthly_months = Vec::new();
+    let mut daily_dates = Vec::new();
+    let mut month_start = NaiveDate::from_ymd_opt(from.year(), from.month(), 1).expect("valid date");
+    while month_start <= to {
+        let month_end = month_last_day(month_start);
+        if month_start >= from && month_end <= to {
+            monthly_months.push(month_start);
+        } else {
+            let mut day = month_start.max(from);
+            let last_day = month_end.min(to);
+            while day <= last_day {
+                daily_dates.push(day);
+                day += Duration::days(1);
+            }
+        }
+        month_start = month_start.checked_add_months(Months::new(1)).expect("date overflow");
+    }
+    RangePlan { monthly_months, daily_dates }
+}
+
+/// Swap a `daily/` pattern for its `monthly/` counterpart, e.g.
+/// `"data/spot/daily/klines/SYMBOL/1m/"` -> `"data/spot/monthly/klines/SYMBOL/1m/"`.
+fn monthly_pattern(daily_pattern: &str) -> String {
+    daily_pattern.replacen("daily", "monthly", 1)
+}
+
+/// The chronologically-sorted URL sequence [`BinanceVisionClient::fetch_range`]
+/// resolved for a date range, plus any day it couldn't find an archive for
+/// (each such day is a gap in `urls`).
+#[derive(Debug, Clone, Default)]
+pub struct FetchRangeResult {
+    pub urls: Vec<String>,
+    /// `YYYY-MM-DD` dates inside the requested range with no archive found,
+    /// either because a whole month's `monthly/` archive was missing or
+    /// because an individual `daily/` archive was missing.
+    pub missing_days: Vec<String>,
+}
+
+/// Infer the archive type from a path pattern's segments, e.g. `"aggTrades"`
+/// in `data/spot/daily/aggTrades/SYMBOL/`, falling back to `Klines`.
+pub fn data_type_from_pattern(pattern: &str) -> BinanceDataType {
+    pattern
+        .split('/')
+        .find_map(BinanceDataType::from_token)
+        .unwrap_or(BinanceDataType::Klines)
+}
+
+/// Binance's spot `exchangeInfo` REST endpoint, for use with
+/// [`BinanceVisionClient::symbols_from_exchange_info`].
+pub const SPOT_EXCHANGE_INFO_URL: &str = "https://api.binance.com/api/v3/exchangeInfo";
+/// Binance's USDⓈ-M futures `exchangeInfo` REST endpoint, for use with
+/// [`BinanceVisionClient::symbols_from_exchange_info`].
+pub const FUTURES_EXCHANGE_INFO_URL: &str = "https://fapi.binance.com/fapi/v1/exchangeInfo";
+
+/// Which symbol lifecycle states [`MarketFilter`] accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolStatus {
+    /// Accept symbols regardless of their `exchangeInfo` status.
+    Any,
+    /// Only symbols whose `exchangeInfo` status is `"TRADING"`.
+    TradingOnly,
+}
+
+/// Criteria for resolving a canonical, deduplicated symbol set from
+/// Binance's live `exchangeInfo` endpoint, as an alternative (or complement,
+/// via intersection with [`BinanceVisionClient::list_symbols`]) to globbing
+/// the bucket's directory listing -- which can't filter by contract status
+/// or quote asset, and includes delisted symbols that happen to still have
+/// archived data.
+#[derive(Debug, Clone)]
+pub struct MarketFilter {
+    /// Only symbols quoted in this asset, e.g. `"USDT"` (case-insensitive).
+    /// `None` accepts any quote asset.
+    pub quote_asset: Option<String>,
+    pub status: SymbolStatus,
+}
+
+impl Default for MarketFilter {
+    fn default() -> Self {
+        Self {
+            quote_asset: None,
+            status: SymbolStatus::TradingOnly,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ExchangeInfoResponse {
+    symbols: Vec<ExchangeSymbol>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExchangeSymbol {
+    symbol: String,
+    #[serde(rename = "quoteAsset")]
+    quote_asset: String,
+    status: String,
+}
+
+fn filter_exchange_symbols(symbols: &[ExchangeSymbol], filter: &MarketFilter) -> Vec<String> {
+    let mut matched: Vec<String> = symbols
+        .iter()
+        .filter(|s| filter.status != SymbolStatus::TradingOnly || s.status == "TRADING")
+        .filter(|s| {
+            filter
+                .quote_asset
+                .as_deref()
+                .map_or(true, |quote| s.quote_asset.eq_ignore_ascii_case(quote))
+        })
+        .map(|s| s.symbol.clone())
+        .collect();
+    matched.sort();
+    matched.dedup();
+    matched
+}
+
+/// Configuration for a [`BinanceVisionClient`]: which bucket to talk to, how
+/// to reach it, and how aggressively to chunk/verify transfers.
+pub struct BinanceVisionClientConfig {
+    /// Root of the `data.binance.vision` bucket to list and download from.
+    pub base_url: String,
+    /// Optional HTTP(S) proxy for the listing client, as read from
+    /// `BINANCE_S3_PROXY` by the CLI.
+    pub listing_proxy: Option<String>,
+    /// Buffer size for [`download_one`]'s in-memory reads.
+    pub chunk_bytes: usize,
+    /// How [`BinanceVisionClient::download_to_parquet`] should treat a
+    /// `.CHECKSUM` mismatch.
+    pub checksum_policy: ChecksumPolicy,
+    /// Columns [`BinanceVisionClient::download_to_parquet`] should keep on
+    /// top of `data_type`'s defaults; see [`resolve_columns`].
+    pub include_columns: Option<Vec<String>>,
+    /// Columns [`BinanceVisionClient::download_to_parquet`] should drop from
+    /// `data_type`'s defaults; see [`resolve_columns`].
+    pub exclude_columns: Option<Vec<String>>,
+}
+
+impl Default for BinanceVisionClientConfig {
+    fn default() -> Self {
+        Self {
+            base_url: BASE_URL.to_string(),
+            listing_proxy: None,
+            chunk_bytes: 1024 * 1024,
+            checksum_policy: ChecksumPolicy::FailOnMismatch,
+            include_columns: None,
+            exclude_columns: None,
+        }
+    }
+}
+
+/// Programmatic entry point to the Binance Vision bucket: owns the listing
+/// and download `Client`s and drives discovery/download/clean the same way
+/// the `main` CLI does, so embedders don't have to go through env vars.
+pub struct BinanceVisionClient {
+    listing_client: Client,
+    download_client: Client,
+    config: BinanceVisionClientConfig,
+}
+
+impl BinanceVisionClient {
+    pub fn new(config: BinanceVisionClientConfig) -> Result<Self> {
+        let download_client = Client::builder().build().context("build download client")?;
+        let listing_client = build_listing_client(config.listing_proxy.as_deref())
+            .context("build listing client")?;
+        Ok(Self {
+            listing_client,
+            download_client,
+            config,
+        })
+    }
+
+    /// Symbols published under `prefix` (the portion of a pattern before
+    /// `SYMBOL`) whose name matches `glob`.
+    pub fn list_symbols(&self, prefix: &str, glob: &str) -> Result<Vec<String>> {
+        let entries = list_prefix_with_base(&self.listing_client, &self.config.base_url, prefix)?;
+        Ok(entries
+            .into_iter()
+            .filter(|entry| entry.1 && wildcard_match(&entry.0, glob))
+            .map(|entry| entry.0)
+            .collect())
+    }
+
+    /// Canonical, deduplicated symbols matching `filter`, resolved from
+    /// Binance's live `exchangeInfo` endpoint (`exchange_info_url`, e.g.
+    /// [`SPOT_EXCHANGE_INFO_URL`] or [`FUTURES_EXCHANGE_INFO_URL`]) rather
+    /// than globbing the bucket's directory listing. Intersect the result
+    /// with [`list_symbols`] to restrict an existing glob-based symbol set
+    /// to currently-trading symbols in a given quote asset.
+    ///
+    /// [`list_symbols`]: BinanceVisionClient::list_symbols
+    pub fn symbols_from_exchange_info(
+        &self,
+        exchange_info_url: &str,
+        filter: &MarketFilter,
+    ) -> Result<Vec<String>> {
+        let body = self
+            .listing_client
+            .get(exchange_info_url)
+            .send()?
+            .text()?;
+        let response: ExchangeInfoResponse =
+            serde_json::from_str(&body).context("parse exchangeInfo response")?;
+        Ok(filter_exchange_symbols(&response.symbols, filter))
+    }
+
+    /// One ListObjectsV2-style page of `query`, bounded by `query.max_keys`
+    /// and resumable via `query.continuation_token` -- unlike
+    /// [`list_symbols`]/[`list_files`], which eagerly drain an entire
+    /// prefix's V1 listing.
+    ///
+    /// [`list_symbols`]: BinanceVisionClient::list_symbols
+    /// [`list_files`]: BinanceVisionClient::list_files
+    pub fn list_prefix_page(&self, query: &ListQuery) -> Result<ListPage> {
+        let bucket_url = get_bucket_url_with_base(&self.listing_client, &self.config.base_url, &query.prefix)?;
+        let mut params = format!("list-type=2&prefix={}", encode(&query.prefix));
+        if let Some(delimiter) = &query.delimiter {
+            params.push_str(&format!("&delimiter={}", encode(delimiter)));
+        }
+        if query.max_keys > 0 {
+            params.push_str(&format!("&max-keys={}", query.max_keys));
+        }
+        if let Some(start_after) = &query.start_after {
+            params.push_str(&format!("&start-after={}", encode(start_after)));
+        }
+        if let Some(token) = &query.continuation_token {
+            params.push_str(&format!("&continuation-token={}", encode(token)));
+        }
+        let request_url = format!("{}?{}", bucket_url, params);
+        let xml_content = self.listing_client.get(request_url).send()?.text()?;
+        parse_listing_v2(&query.prefix, &xml_content)
+    }
+
+    /// Archive URLs published for `symbol` under `pattern` (with `SYMBOL`
+    /// substituted in).
+    pub fn list_files(&self, pattern: &str, symbol: &str) -> Result<Vec<String>> {
+        let path = pattern.replace("SYMBOL", symbol);
+        let entries = list_prefix_with_base(&self.listing_client, &self.config.base_url, &path)?;
+        Ok(entries
+            .into_iter()
+            .filter(|entry| !entry.1)
+            .map(|entry| encoded_url(&path, &entry.0))
+            .collect())
+    }
+
+    /// Every symbol matching `symbol_glob` under `pattern`, and the archive
+    /// URLs published for each -- the same traversal [`list_symbols`] plus
+    /// [`list_files`] would do, in one listing pass.
+    ///
+    /// [`list_symbols`]: BinanceVisionClient::list_symbols
+    /// [`list_files`]: BinanceVisionClient::list_files
+    pub fn discover(&self, pattern: &str, symbol_glob: &str) -> Result<HashMap<String, Vec<String>>> {
+        build_urls_with_base(&self.listing_client, &self.config.base_url, pattern, symbol_glob)
+    }
+
+    /// Resolve `[from, to]` into a chronologically-sorted, de-duplicated
+    /// sequence of archive URLs for `symbol`, using `pattern`'s `monthly/`
+    /// archives for whole months and its `daily/` archives for the
+    /// leading/trailing partial months -- whichever Binance actually
+    /// publishes for a still-incomplete current month. `pattern` must be a
+    /// `daily/` pattern (e.g. `"data/spot/daily/klines/SYMBOL/1m/"`); its
+    /// `monthly/` counterpart is derived automatically.
+    pub fn fetch_range(
+        &self,
+        pattern: &str,
+        symbol: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<FetchRangeResult> {
+        anyhow::ensure!(from <= to, "fetch_range: `from` must not be after `to`");
+        let plan = plan_range(from.date_naive(), to.date_naive());
+
+        let monthly_urls = if plan.monthly_months.is_empty() {
+            Vec::new()
+        } else {
+            self.list_files(&monthly_pattern(pattern), symbol)?
+        };
+        let daily_urls = if plan.daily_dates.is_empty() {
+            Vec::new()
+        } else {
+            self.list_files(pattern, symbol)?
+        };
+
+        let mut dated_urls: Vec<(NaiveDate, String)> = Vec::new();
+        let mut missing_days = Vec::new();
+
+        for month in &plan.monthly_months {
+            let token = month.format("%Y-%m").to_string();
+            match monthly_urls.iter().find(|url| url.contains(&token)) {
+                Some(url) => dated_urls.push((*month, url.clone())),
+                None => {
+                    let mut day = *month;
+                    let last_day = month_last_day(*month);
+                    while day <= last_day {
+                        missing_days.push(day.format("%Y-%m-%d").to_string());
+                        day += Duration::days(1);
+                    }
+                }
+            }
+        }
+        for day in &plan.daily_dates {
+            let token = day.format("%Y-%m-%d").to_string();
+            match daily_urls.iter().find(|url| url.contains(&token)) {
+                Some(url) => dated_urls.push((*day, url.clone())),
+                None => missing_days.push(day.format("%Y-%m-%d").to_string()),
+            }
+        }
+
+        dated_urls.sort_by(|a, b| a.0.cmp(&b.0));
+        dated_urls.dedup_by(|a, b| a.1 == b.1);
+        missing_days.sort();
+
+        Ok(FetchRangeResult {
+            urls: dated_urls.into_iter().map(|(_, url)| url).collect(),
+            missing_days,
+        })
+    }
+
+    /// The `(symbol, url)` pairs in `urls` that aren't already recorded in
+    /// `processed`, by full URL or by bare archive file name.
+    pub fn pending_urls<'a>(
+        &self,
+        urls: &'a HashMap<String, Vec<String>>,
+        processed: &'a HashSet<String>,
+    ) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
+        urls.iter().flat_map(move |(symbol, symbol_urls)| {
+            symbol_urls.iter().filter_map(move |url| {
+                let file_name = extract_zip_name(url);
+                let already_processed = processed.contains(url)
+                    || file_name
+                        .as_ref()
+                        .map(|name| processed.contains(name))
+                        .unwrap_or(false);
+                if already_processed {
+                    None
+                } else {
+                    Some((symbol.as_str(), url.as_str()))
+                }
+            })
+        })
+    }
+
+    /// Download `url` (one archive belonging to `symbol`), verify it against
+    /// its `.CHECKSUM` sidecar unless disabled, and clean it into the
+    /// Hive-partitioned Parquet dataset under `pattern`.
+    pub fn download_to_parquet(
+        &self,
+        pattern: &str,
+        symbol: &str,
+        url: &str,
+        data_type: BinanceDataType,
+    ) -> Result<()> {
+        let dest = raw_download_path(pattern, url)?;
+        download_to_file(&self.download_client, url, &dest)?;
+        if self.config.checksum_policy != ChecksumPolicy::Skip {
+            if let ChecksumOutcome::Mismatch { expected, actual } =
+                verify_checksum(&self.download_client, url, &dest)?
+            {
+                match self.config.checksum_policy {
+                    ChecksumPolicy::FailOnMismatch => {
+                        let _ = fs::remove_file(&dest);
+                        anyhow::bail!(
+                            "checksum mismatch for {} (expected {}, got {})",
+                            url,
+                            expected,
+                            actual
+                        );
+                    }
+                    ChecksumPolicy::WarnOnMismatch => {
+                        println!(
+                            "warning: checksum mismatch for {} (expected {}, got {})",
+                            url, expected, actual
+                        );
+                    }
+                    ChecksumPolicy::Skip => unreachable!(),
+                }
+            }
+        }
+        clean_zip_bytes(
+            &dest,
+            pattern,
+            Some(symbol),
+            data_type,
+            self.config.include_columns.as_deref(),
+            self.config.exclude_columns.as_deref(),
+        )?;
+        let _ = fs::remove_file(&dest);
+        Ok(())
+    }
+
+    /// Fetch `url`'s single-entry archive and write its inner CSV to
+    /// `dest_dir`, skipping the network entirely on a cache hit -- see
+    /// [`download_cached`]. Returns the entry's name inside the archive and
+    /// the path it was written to.
+    ///
+    /// This is a separate surface from [`download_to_parquet`]: that one
+    /// drives potentially multi-entry, multi-hundred-MB monthly archives
+    /// straight into the Hive-partitioned Parquet dataset without ever
+    /// buffering the whole archive in memory. This one is for pulling a
+    /// single day's raw CSV for ad hoc inspection or reuse outside the
+    /// Parquet pipeline, where buffering the archive in memory and caching
+    /// Binance's immutable dated archives on disk, keyed by a hash of the URL,
+    /// pays for itself across repeat runs.
+    ///
+    /// [`download_to_parquet`]: BinanceVisionClient::download_to_parquet
+    pub fn fetch_csv(&self, url: &str, dest_dir: &std::path::Path) -> Result<(String, PathBuf)> {
+        let bytes = download_cached(&self.download_client, url, self.config.chunk_bytes)?;
+        fs::create_dir_all(dest_dir)?;
+        with_single_csv_entry(&bytes, |name, reader| {
+            let dest = dest_dir.join(name);
+            let mut file = fs::File::create(&dest)?;
+            std::io::copy(reader, &mut file)?;
+            Ok((name.to_string(), dest))
+        })
+    }
+
+    /// Time-windowed read over the cleaned Parquet dataset; see [`query_range`].
+    pub fn query_range(
+        &self,
+        pattern: &str,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<DataFrame> {
+        query_range(pattern, symbol, start, end)
+    }
+
+    /// Load the set of already-processed URLs/file names recorded for `pattern`.
+    pub fn load_processed(&self, pattern: &str) -> Result<HashSet<String>> {
+        load_processed_urls(&processed_path(pattern))
+    }
+
+    /// Open the append-only processed-URLs log for `pattern` for concurrent writers.
+    pub fn open_processed_writer(&self, pattern: &str) -> Result<Arc<Mutex<fs::File>>> {
+        open_processed_writer(&processed_path(pattern))
+    }
+
+    /// Record `url` as processed in `pattern`'s processed-URLs log.
+    pub fn record_processed(&self, writer: &Arc<Mutex<fs::File>>, url: &str) -> Result<()> {
+        record_processed(writer, url)
+    }
+
+    /// Sequential alternative to the CLI's `rayon`-parallel download loop,
+    /// kept for embedders that want a single-threaded pass over `urls`.
+    #[allow(dead_code)]
+    pub fn download_all_sequential(
+        &self,
+        pattern: &str,
+        urls: &HashMap<String, Vec<String>>,
+        data_type: BinanceDataType,
+    ) -> Result<()> {
+        let processed_urls = self.load_processed(pattern)?;
+        let processed_writer = self.open_processed_writer(pattern)?;
+        let downloaded = AtomicUsize::new(0);
+        let failed = AtomicUsize::new(0);
+        let mut skipped = 0usize;
+        for (symbol, symbol_urls) in urls {
+            for url in symbol_urls {
+                let file_name = extract_zip_name(url);
+                let already_processed = processed_urls.contains(url)
+                    || file_name
+                        .as_ref()
+                        .map(|name| processed_urls.contains(name))
+                        .unwrap_or(false);
+                if already_processed {
+                    skipped += 1;
+                    continue;
+                }
+                match self.download_to_parquet(pattern, symbol, url, data_type) {
+                    Ok(()) => {
+                        self.record_processed(&processed_writer, url)?;
+                        downloaded.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(_) => {
+                        failed.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+        }
+        println!(
+            "Processed: {}, Failed: {}, Skipped: {}",
+            downloaded.load(Ordering::Relaxed),
+            failed.load(Ordering::Relaxed),
+            skipped
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::Arc;
+    use std::thread;
+
+    fn serve_once(listener: TcpListener, handler: Arc<dyn Fn(String) -> String + Send + Sync>) -> String {
+        let addr = listener.local_addr().expect("addr");
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                handle_stream(stream, handler.clone());
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    fn handle_stream(mut stream: TcpStream, handler: Arc<dyn Fn(String) -> String + Send + Sync>) {
+        let mut buffer = [0u8; 2048];
+        let read = stream.read(&mut buffer).unwrap_or(0);
+        let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+        let path_line = request.lines().next().unwrap_or_default();
+        let path = path_line
+            .split_whitespace()
+            .nth(1)
+            .unwrap_or("/")
+            .to_string();
+        let response_body = handler(path);
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+            response_body.len(),
+            response_body
+        );
+        let _ = stream.write_all(response.as_bytes());
+    }
+
+    /// Like [`serve_once`], but for handlers whose response body isn't valid
+    /// UTF-8 (e.g. a real zip archive).
+    fn serve_once_bytes(listener: TcpListener, handler: Arc<dyn Fn(String) -> Vec<u8> + Send + Sync>) -> String {
+        let addr = listener.local_addr().expect("addr");
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                handle_stream_bytes(stream, handler.clone());
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    fn handle_stream_bytes(mut stream: TcpStream, handler: Arc<dyn Fn(String) -> Vec<u8> + Send + Sync>) {
+        let mut buffer = [0u8; 2048];
+        let read = stream.read(&mut buffer).unwrap_or(0);
+        let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+        let path_line = request.lines().next().unwrap_or_default();
+        let path = path_line
+            .split_whitespace()
+            .nth(1)
+            .unwrap_or("/")
+            .to_string();
+        let body = handler(path);
+        let mut response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", body.len()).into_bytes();
+        response.extend_from_slice(&body);
+        let _ = stream.write_all(&response);
+    }
+
+    #[test]
+    fn builds_reader_schema_for_agg_trades() {
+        let schema = reader_schema(BinanceDataType::AggTrades);
+        assert_eq!(
+            schema.iter_names().map(|n| n.as_str()).collect::<Vec<_>>(),
+            BinanceDataType::AggTrades.columns()
+        );
+        assert_eq!(
+            schema.get("price").map(|dt| dt.clone()),
+            Some(PolarsDataType::Float64)
+        );
+    }
+
+    #[test]
+    fn reader_schema_casts_epoch_columns_to_int64() {
+        let schema = reader_schema(BinanceDataType::Klines);
+        assert_eq!(
+            schema.get("open_time").map(|dt| dt.clone()),
+            Some(PolarsDataType::Int64)
+        );
+        assert_eq!(
+            schema.get("close").map(|dt| dt.clone()),
+            Some(PolarsDataType::Float64)
+        );
+    }
+
+    #[test]
+    fn checksum_policy_from_env_parses_known_values() {
+        env::set_var("BINANCE_VERIFY_CHECKSUM", "0");
+        assert_eq!(checksum_policy_from_env(), ChecksumPolicy::Skip);
+        env::set_var("BINANCE_VERIFY_CHECKSUM", "false");
+        assert_eq!(checksum_policy_from_env(), ChecksumPolicy::Skip);
+        env::set_var("BINANCE_VERIFY_CHECKSUM", "warn");
+        assert_eq!(checksum_policy_from_env(), ChecksumPolicy::WarnOnMismatch);
+        env::set_var("BINANCE_VERIFY_CHECKSUM", "1");
+        assert_eq!(checksum_policy_from_env(), ChecksumPolicy::FailOnMismatch);
+        env::remove_var("BINANCE_VERIFY_CHECKSUM");
+        assert_eq!(checksum_policy_from_env(), ChecksumPolicy::FailOnMismatch);
+    }
+
+    #[test]
+    fn filters_exchange_symbols_by_quote_asset_and_status() {
+        let symbols = vec![
+            ExchangeSymbol {
+                symbol: "BTCUSDT".to_string(),
+                quote_asset: "USDT".to_string(),
+                status: "TRADING".to_string(),
+            },
+            ExchangeSymbol {
+                symbol: "ETHUSDT".to_string(),
+                quote_asset: "USDT".to_string(),
+                status: "BREAK".to_string(),
+            },
+            ExchangeSymbol {
+                symbol: "BTCBUSD".to_string(),
+                quote_asset: "BUSD".to_string(),
+                status: "TRADING".to_string(),
+            },
+        ];
+
+        let filter = MarketFilter {
+            quote_asset: Some("usdt".to_string()),
+            status: SymbolStatus::TradingOnly,
+        };
+        assert_eq!(filter_exchange_symbols(&symbols, &filter), vec!["BTCUSDT".to_string()]);
+
+        let any_status = MarketFilter {
+            quote_asset: Some("USDT".to_string()),
+            status: SymbolStatus::Any,
+        };
+        assert_eq!(
+            filter_exchange_symbols(&symbols, &any_status),
+            vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()]
+        );
+    }
+
+    #[test]
+    fn plans_whole_months_as_monthly_and_edges_as_daily() {
+        let from = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let to = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let plan = plan_range(from, to);
+        assert_eq!(plan.monthly_months, vec![NaiveDate::from_ymd_opt(2024, 2, 1).unwrap()]);
+        assert_eq!(plan.daily_dates.len(), 17 + 10);
+        assert_eq!(plan.daily_dates.first(), Some(&from));
+        assert_eq!(plan.daily_dates.last(), Some(&to));
+    }
+
+    #[test]
+    fn plans_single_whole_month_as_monthly_only() {
+        let from = NaiveDate::from_ymd_opt(2024, 2, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        let plan = plan_range(from, to);
+        assert_eq!(plan.monthly_months, vec![from]);
+        assert!(plan.daily_dates.is_empty());
+    }
+
+    #[test]
+    fn derives_monthly_pattern_from_daily_pattern() {
+        assert_eq!(
+            monthly_pattern("data/spot/daily/klines/SYMBOL/1m/"),
+            "data/spot/monthly/klines/SYMBOL/1m/"
+        );
+    }
+
+    #[test]
+    fn builds_pattern_per_market_and_data_type() {
+        assert_eq!(
+            build_pattern(MarketType::Spot, BinanceDataType::Klines, Some("1m")),
+            "data/spot/daily/klines/SYMBOL/1m/"
+        );
+        assert_eq!(
+            build_pattern(MarketType::UsdMFutures, BinanceDataType::FundingRate, None),
+            "data/futures/um/daily/fundingRate/SYMBOL/"
+        );
+        assert_eq!(
+            build_pattern(MarketType::CoinMFutures, BinanceDataType::PremiumIndexKlines, Some("5m")),
+            "data/futures/cm/daily/premiumIndexKlines/SYMBOL/5m/"
+        );
+    }
+
+    #[test]
+    fn infers_data_type_from_pattern() {
+        assert_eq!(
+            data_type_from_pattern("data/spot/daily/klines/SYMBOL/1m/"),
+            BinanceDataType::Klines
+        );
+        assert_eq!(
+            data_type_from_pattern("data/spot/daily/aggTrades/SYMBOL/"),
+            BinanceDataType::AggTrades
+        );
+        assert_eq!(
+            data_type_from_pattern("data/spot/daily/unknown/SYMBOL/"),
+            BinanceDataType::Klines
+        );
+    }
+
+    #[test]
+    fn derives_symbol_from_archive_entry_names() {
+        assert_eq!(
+            derive_symbol_from_filename("BTCUSDT-1m-2024-01-01.csv"),
+            Some("BTCUSDT".to_string())
+        );
+        assert_eq!(
+            derive_symbol_from_filename("path/ETHUSDT-trades-2024-01-01.csv"),
+            Some("ETHUSDT".to_string())
+        );
+        assert_eq!(derive_symbol_from_filename(""), None);
+    }
+
+    #[test]
+    fn resolves_columns_with_excludes_and_includes() {
+        let exclude = vec!["ignore".to_string(), "close_time".to_string()];
+        let columns = resolve_columns(BinanceDataType::Klines, None, Some(&exclude)).unwrap();
+        assert!(!columns.contains(&"ignore".to_string()));
+        assert!(!columns.contains(&"close_time".to_string()));
+        assert_eq!(columns.len(), BinanceDataType::Klines.columns().len() - 2);
+
+        let include = vec!["close_time".to_string()];
+        let columns = resolve_columns(BinanceDataType::Klines, Some(&include), Some(&exclude)).unwrap();
+        assert!(columns.contains(&"close_time".to_string()), "include wins over exclude");
+        assert!(!columns.contains(&"ignore".to_string()));
+    }
+
+    #[test]
+    fn resolve_columns_rejects_unknown_names() {
+        let exclude = vec!["not_a_real_column".to_string()];
+        let err = resolve_columns(BinanceDataType::Klines, None, Some(&exclude)).unwrap_err();
+        assert!(err.to_string().contains("unknown column"));
+    }
+
+    #[test]
+    fn derives_partition_date_from_filenames() {
+        assert_eq!(
+            derive_date_from_filename("BTCUSDT-1m-2024-01-15.csv"),
+            Some("2024-01-15".to_string())
+        );
+        assert_eq!(
+            derive_date_from_filename("BTCUSDT-1m-2024-01.csv"),
+            Some("2024-01".to_string())
+        );
+        assert_eq!(derive_date_from_filename("no-date-here.csv"), None);
+    }
+
+    #[test]
+    fn builds_hive_style_partition_dir() {
+        let dir = partition_dir("data/spot/daily/klines/SYMBOL/1m/", "BTCUSDT", "2024-01-15");
+        assert_eq!(
+            dir,
+            PathBuf::from(CLEAN_ROOT)
+                .join("data/spot/daily/klines/SYMBOL/1m/")
+                .join("symbol=BTCUSDT")
+                .join("date=2024-01-15")
+        );
+    }
+
+    #[test]
+    fn parses_checksum_sidecar_body() {
+        let body = "2c624232cdd221771294dfbb310aca000a0df6ac8b66b696d90ef06f74b4e96  BTCUSDT-1m-2024-01-01.zip\n";
+        assert_eq!(
+            parse_checksum_body(body),
+            Some("2c624232cdd221771294dfbb310aca000a0df6ac8b66b696d90ef06f74b4e96".to_string())
+        );
+        assert_eq!(parse_checksum_body(""), None);
+    }
+
+    #[test]
+    fn hashes_bytes_to_known_sha256() {
+        // sha256("") = e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn verifies_checksum_against_local_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let body = b"zip-bytes";
+        let digest = sha256_hex(body);
+        let handler = Arc::new(move |path: String| {
+            if path.contains("CHECKSUM") {
+                format!("{}  file.zip", digest)
+            } else {
+                "".to_string()
+            }
+        });
+        let base_url = serve_once(listener, handler);
+        let client = ClientBuilder::new().no_proxy().build().unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let downloaded_path = temp_dir.path().join("file.zip");
+        fs::write(&downloaded_path, body).unwrap();
+        let outcome = verify_checksum(&client, &format!("{}/file.zip", base_url), &downloaded_path).unwrap();
+        assert!(matches!(outcome, ChecksumOutcome::Verified));
+    }
+
+    #[test]
+    fn detects_header() {
+        let csv_with_header = "open_time,open,high\n1,2,3\n";
+        let csv_without_header = "1,2,3\n4,5,6\n";
+        assert!(has_header(csv_with_header));
+        assert!(!has_header(csv_without_header));
+    }
+
+    #[test]
+    fn matches_wildcards() {
+        assert!(wildcard_match("BTCUSDT", "*USDT"));
+        assert!(wildcard_match("ETHBTC", "ETH*"));
+        assert!(!wildcard_match("BNBUSDT", "BTC*"));
+    }
+
+    #[test]
+    fn parses_listing_entries() {
+        let prefix = "data/spot/daily/klines/SYMBOL/1m/";
+        let xml = r#"
+            <ListBucketResult>
+              <CommonPrefixes><Prefix>data/spot/daily/klines/SYMBOL/1m/BTCUSDT/</Prefix></CommonPrefixes>
+              <Contents><Key>data/spot/daily/klines/SYMBOL/1m/BTCUSDT/BTCUSDT-1m-2024-01-01.zip</Key></Contents>
+              <IsTruncated>false</IsTruncated>
+            </ListBucketResult>
+        "#;
+        let (entries, truncated, next_marker) = parse_listing(prefix, xml).unwrap();
+        assert!(!truncated);
+        assert!(next_marker.is_none());
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].0, "BTCUSDT");
+        assert!(entries[0].1);
+    }
+
+    #[test]
+    fn parses_v2_listing_with_continuation_token() {
+        let prefix = "data/spot/daily/klines/SYMBOL/1m/";
+        let xml = r#"
+            <ListBucketResult>
+              <KeyCount>2</KeyCount>
+              <CommonPrefixes><Prefix>data/spot/daily/klines/SYMBOL/1m/BTCUSDT/</Prefix></CommonPrefixes>
+              <Contents><Key>data/spot/daily/klines/SYMBOL/1m/BTCUSDT/BTCUSDT-1m-2024-01-01.zip</Key></Contents>
+              <IsTruncated>true</IsTruncated>
+              <NextContinuationToken>abc123</NextContinuationToken>
+            </ListBucketResult>
+        "#;
+        let page = parse_listing_v2(prefix, xml).unwrap();
+        assert!(page.is_truncated);
+        assert_eq!(page.key_count, 2);
+        assert_eq!(page.next_continuation_token, Some("abc123".to_string()));
+        assert_eq!(page.entries.len(), 2);
+    }
+
+    #[test]
+    fn v2_listing_drops_continuation_token_when_not_truncated() {
+        let xml = r#"
+            <ListBucketResult>
+              <KeyCount>1</KeyCount>
+              <IsTruncated>false</IsTruncated>
+            </ListBucketResult>
+        "#;
+        let page = parse_listing_v2("data/spot/daily/klines/SYMBOL/1m/", xml).unwrap();
+        assert!(!page.is_truncated);
+        assert!(page.next_continuation_token.is_none());
+    }
+
+    #[test]
+    fn encodes_url() {
+        let url = encoded_url("data/spot/daily/klines/SYMBOL/1m/", "BTCUSDT-1m-2024-01-01.zip");
+        assert!(url.contains("data/spot/daily/klines/SYMBOL/1m/BTCUSDT-1m-2024-01-01.zip"));
+    }
+
+    #[test]
+    fn normalizes_frames() {
+        let df = df![
+            "open_time" => [2i64, 1i64, 1i64],
+            "price" => [10i64, 20i64, 20i64]
+        ]
+        .unwrap();
+        let normalized = normalize_frame(df).unwrap();
+        let times = normalized.column("open_time").unwrap().i64().unwrap();
+        assert_eq!(times.get(0), Some(1));
+        assert_eq!(times.len(), 2);
+    }
+
+    #[test]
+    fn queries_range_on_non_klines_time_column() {
+        use chrono::TimeZone;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let original_dir = env::current_dir().unwrap();
+        env::set_current_dir(temp_dir.path()).unwrap();
+
+        let pattern = "data/spot/daily/aggTrades/SYMBOL/";
+        let df = df![
+            "agg_trade_id" => [1i64, 2i64, 3i64],
+            "transact_time" => [1_000i64, 2_000i64, 3_000i64]
+        ]
+        .unwrap();
+        write_partition(pattern, "BTCUSDT", "2024-01-01", df).unwrap();
+
+        let result = query_range(
+            pattern,
+            "BTCUSDT",
+            Utc.timestamp_millis_opt(1_500).unwrap(),
+            Utc.timestamp_millis_opt(2_500).unwrap(),
+        );
+
+        env::set_current_dir(original_dir).unwrap();
+        let result = result.unwrap();
+
+        assert_eq!(
+            result
+                .column("transact_time")
+                .unwrap()
+                .i64()
+                .unwrap()
+                .into_no_null_iter()
+                .collect::<Vec<_>>(),
+            vec![2_000]
+        );
+    }
+
+    #[test]
+    fn loads_processed_urls_from_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("processed.txt");
+        fs::write(&path, "http://example.com/a file-a.zip\nnot-a-url\nhttp://example.com/b\n").unwrap();
+        let urls = load_processed_urls(&path).unwrap();
+        assert!(urls.contains("http://example.com/a"));
+        assert!(urls.contains("file-a.zip"));
+        assert!(urls.contains("http://example.com/b"));
+        assert_eq!(urls.len(), 4);
+    }
+
+    #[test]
+    fn records_processed_url() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("processed.txt");
+        let writer = open_processed_writer(&path).unwrap();
+        record_processed(&writer, "http://example.com/a.zip").unwrap();
+        record_processed(&writer, "http://example.com/b").unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("http://example.com/a.zip"));
+        assert!(contents.contains("a.zip"));
+        assert!(contents.contains("http://example.com/b"));
+    }
+
+    #[test]
+    fn extracts_zip_name_from_url() {
+        let name = extract_zip_name("http://example.com/path/data.zip?foo=bar").unwrap();
+        assert_eq!(name, "data.zip");
+    }
+
+    #[test]
+    fn builds_listing_client_with_proxy() {
+        let client = build_listing_client(Some("http://127.0.0.1:1234")).unwrap();
+        let url = encoded_url("data/spot/daily/klines/SYMBOL/1m/", "BTCUSDT-1m-2024-01-01.zip");
+        assert!(client.get(url).build().is_ok());
+    }
+
+    #[test]
+    fn downloads_from_local_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = serve_once(listener, Arc::new(|_path| "zip-bytes".to_string()));
+        let client = ClientBuilder::new().no_proxy().build().unwrap();
+        let bytes = download_one(&client, &format!("{}/file.zip", base_url), 4).unwrap();
+        assert_eq!(bytes, b"zip-bytes");
+    }
+
+    #[test]
+    fn download_verified_catches_checksum_mismatch() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let handler = Arc::new(move |path: String| {
+            if path.contains("CHECKSUM") {
+                "0000000000000000000000000000000000000000000000000000000000000000  file.zip".to_string()
+            } else {
+                "zip-bytes".to_string()
+            }
+        });
+        let base_url = serve_once(listener, handler);
+        let client = ClientBuilder::new().no_proxy().build().unwrap();
+        let err = download_verified(&client, &format!("{}/file.zip", base_url), 4).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"));
+    }
+
+    #[test]
+    fn download_cached_reuses_disk_on_second_call() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        env::set_var("XDG_CACHE_HOME", temp_dir.path());
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counted_hits = hits.clone();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let digest = sha256_hex(b"zip-bytes");
+        let handler = Arc::new(move |path: String| {
+            if path.contains("CHECKSUM") {
+                format!("{}  file.zip", digest)
+            } else {
+                counted_hits.fetch_add(1, Ordering::SeqCst);
+                "zip-bytes".to_string()
+            }
+        });
+        let base_url = serve_once(listener, handler);
+        let client = ClientBuilder::new().no_proxy().build().unwrap();
+        let url = format!("{}/file.zip", base_url);
+
+        let first = download_cached(&client, &url, 4).unwrap();
+        let second = download_cached(&client, &url, 4).unwrap();
+
+        assert_eq!(first, b"zip-bytes");
+        assert_eq!(second, b"zip-bytes");
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        env::remove_var("XDG_CACHE_HOME");
+    }
+
+    #[test]
+    fn fetch_csv_writes_cached_csv_to_disk() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        env::set_var("XDG_CACHE_HOME", cache_dir.path());
+
+        let archive = zip_with_entries(&[("BTCUSDT-1m-2024-01-01.csv", "1,2,3\n")]);
+        let digest = sha256_hex(&archive);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let handler: Arc<dyn Fn(String) -> Vec<u8> + Send + Sync> = Arc::new(move |path: String| {
+            if path.contains("CHECKSUM") {
+                format!("{}  BTCUSDT-1m-2024-01-01.zip", digest).into_bytes()
+            } else {
+                archive.clone()
+            }
+        });
+        let base_url = serve_once_bytes(listener, handler);
+
+        let client = BinanceVisionClient::new(BinanceVisionClientConfig::default()).unwrap();
+        let dest_dir = tempfile::tempdir().unwrap();
+        let (name, path) = client
+            .fetch_csv(&format!("{}/BTCUSDT-1m-2024-01-01.zip", base_url), dest_dir.path())
+            .unwrap();
+
+        assert_eq!(name, "BTCUSDT-1m-2024-01-01.csv");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1,2,3\n");
+        env::remove_var("XDG_CACHE_HOME");
+    }
+
+    fn zip_with_entries(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = ::zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, contents) in entries {
+            writer.start_file(*name, ::zip::write::FileOptions::default()).unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn streams_single_csv_entry() {
+        let archive = zip_with_entries(&[("BTCUSDT-1m-2024-01-01.csv", "1,2,3\n")]);
+        let mut collected = String::new();
+        let name = with_single_csv_entry(&archive, |name, reader| {
+            reader.read_to_string(&mut collected)?;
+            Ok(name.to_string())
+        })
+        .unwrap();
+        assert_eq!(name, "BTCUSDT-1m-2024-01-01.csv");
+        assert_eq!(collected, "1,2,3\n");
+    }
+
+    #[test]
+    fn rejects_multi_entry_archive() {
+        let archive = zip_with_entries(&[("a.csv", "1\n"), ("b.csv", "2\n")]);
+        let err = with_single_csv_entry(&archive, |_, _| Ok(())).unwrap_err();
+        assert!(err.to_string().contains("single-entry"));
+    }
+
+    #[test]
+    fn rejects_non_csv_entry() {
+        let archive = zip_with_entries(&[("readme.txt", "not a csv")]);
+        let err = with_single_csv_entry(&archive, |_, _| Ok(())).unwrap_err();
+        assert!(err.to_string().contains("not a .csv file"));
+    }
+
+    #[test]
+    fn downloads_to_file_and_leaves_no_part_file() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = serve_once(listener, Arc::new(|_path| "zip-bytes".to_string()));
+        let client = ClientBuilder::new().no_proxy().build().unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dest = temp_dir.path().join("file.zip");
+        download_to_file(&client, &format!("{}/file.zip", base_url), &dest).unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), b"zip-bytes");
+        assert!(!PathBuf::from(format!("{}.part", dest.display())).exists());
+    }
+
+    #[test]
+    fn pending_urls_skips_processed_by_url_or_file_name() {
+        let client = BinanceVisionClient::new(BinanceVisionClientConfig::default()).unwrap();
+        let mut urls = HashMap::new();
+        urls.insert(
+            "BTCUSDT".to_string(),
+            vec![
+                "http://example.com/BTCUSDT-1m-2024-01-01.zip".to_string(),
+                "http://example.com/BTCUSDT-1m-2024-01-02.zip".to_string(),
+            ],
+        );
+        let mut processed = HashSet::new();
+        processed.insert("http://example.com/BTCUSDT-1m-2024-01-01.zip".to_string());
+        processed.insert("BTCUSDT-1m-2024-01-02.zip".to_string());
+        let pending: Vec<_> = client.pending_urls(&urls, &processed).collect();
+        assert!(pending.is_empty());
+
+        processed.clear();
+        let pending: Vec<_> = client.pending_urls(&urls, &processed).collect();
+        assert_eq!(pending.len(), 2);
+    }
+
+    #[test]
+    fn gets_bucket_url_from_listing_page() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let handler = Arc::new(move |path: String| {
+            if path.starts_with("/?prefix=") {
+                format!("var BUCKET_URL = '{}/bucket';", base_url)
+            } else {
+                "".to_string()
+            }
+        });
+        let base_url = serve_once(listener, handler);
+        let client = ClientBuilder::new().no_proxy().build().unwrap();
+        let prefix = "data/spot/";
+        let url = get_bucket_url_with_base(&client, &base_url, prefix).unwrap();
+        assert_eq!(url, format!("{}/bucket", base_url));
+    }
+
+    #[test]
+    fn lists_prefix_entries() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let handler = Arc::new(move |path: String| {
+            if path.starts_with("/?prefix=") {
+                format!("var BUCKET_URL = '{}/bucket';", base_url)
+            } else if path.starts_with("/bucket") {
+                r#"<ListBucketResult>
+                        <CommonPrefixes><Prefix>data/spot/daily/klines/SYMBOL/1m/BTCUSDT/</Prefix></CommonPrefixes>
+                        <Contents><Key>data/spot/daily/klines/SYMBOL/1m/BTCUSDT/BTCUSDT-1m-2024-01-01.zip</Key></Contents>
+                        <IsTruncated>false</IsTruncated>
+                    </ListBucketResult>"#
+                    .to_string()
+            } else {
+                "".to_string()
+            }
+        });
+        let base_url = serve_once(listener, handler);
+        let client = ClientBuilder::new().no_proxy().build().unwrap();
+        let prefix = "data/spot/daily/klines/SYMBOL/1m/";
+        let entries = list_prefix_with_base(&client, &base_url, prefix).unwrap_or_default();
+        assert!(!entries.is_empty());
+    }
+
+    #[test]
+    fn builds_urls_from_listing() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let listing_page = format!("var BUCKET_URL = '{}/bucket';", base_url);
+        let symbols_xml = r#"<ListBucketResult>
+                <CommonPrefixes><Prefix>data/spot/daily/klines/SYMBOL/1m/BTCUSDT/</Prefix></CommonPrefixes>
+                <IsTruncated>false</IsTruncated>
+            </ListBucketResult>"#
+            .to_string();
+        let zips_xml = r#"<ListBucketResult>
+                <Contents><Key>data/spot/daily/klines/SYMBOL/1m/BTCUSDT/BTCUSDT-1m-2024-01-01.zip</Key></Contents>
+                <IsTruncated>false</IsTruncated>
+            </ListBucketResult>"#
+            .to_string();
+        let handler = Arc::new(move |path: String| {
+            if path.starts_with("/?prefix=") {
+                listing_page.clone()
+            } else if path.starts_with("/bucket") {
+                if path.contains("BTCUSDT") {
+                    zips_xml.clone()
+                } else {
+                    symbols_xml.clone()
+                }
+            } else {
+                "".to_string()
+            }
+        });
+        let base_url = serve_once(listener, handler);
+        let client = ClientBuilder::new().no_proxy().build().unwrap();
+        let pattern = "data/spot/daily/klines/SYMBOL/1m/";
+        let urls = build_urls_with_base(&client, &base_url, pattern, "*USDT").unwrap_or_default();
+        assert!(urls.values().flatten().any(|url| url.contains("BTCUSDT-1m-2024-01-01.zip")));
+    }
+}
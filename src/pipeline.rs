@@ -0,0 +1,190 @@
+//! Concurrent, resumable archive downloader built on async reqwest, for
+//! staging thousands of URLs (e.g. every 1m kline for all USDT pairs over
+//! several years) to disk without hand-rolling task/concurrency management.
+//!
+//! This is a separate surface from [`crate::BinanceVisionClient`]'s
+//! synchronous, rayon-parallel download path -- that one drives archives
+//! straight into the Parquet-cleaning pipeline one symbol at a time; this
+//! one just gets raw bytes to `dest_dir` as fast as a bounded concurrency
+//! limit safely allows, for callers staging a very large pull up front.
+
+use anyhow::{Context, Result};
+use futures::stream::{self, Stream, StreamExt};
+use reqwest::Client;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Semaphore;
+
+/// One archive [`DownloadPlan::execute`] finished writing to disk.
+#[derive(Debug, Clone)]
+pub struct DownloadedFile {
+    pub symbol: String,
+    pub url: String,
+    pub path: PathBuf,
+    pub bytes: u64,
+}
+
+/// A batch of URLs to fetch concurrently, grouped by symbol the same way
+/// [`crate::BinanceVisionClient::discover`] groups them.
+pub struct DownloadPlan {
+    urls: HashMap<String, Vec<String>>,
+    client: Client,
+}
+
+impl DownloadPlan {
+    /// Build a plan from a `symbol -> urls` map, e.g. the output of
+    /// [`crate::BinanceVisionClient::discover`].
+    pub fn new(urls: HashMap<String, Vec<String>>) -> Result<Self> {
+        let client = Client::builder().build().context("build async download client")?;
+        Ok(Self { urls, client })
+    }
+
+    /// Download every URL into `dest_dir`, honoring at most `concurrency`
+    /// in-flight requests at a time (semaphore-bounded via `buffer_unordered`),
+    /// resuming partially-downloaded `.zip` files via `Range` requests and
+    /// retrying transient failures with the same exponential backoff as the
+    /// synchronous pipeline (`BINANCE_MAX_RETRIES`/`BINANCE_RETRY_BASE_MS`).
+    /// Each item resolves independently as it completes, so one symbol's
+    /// failure never blocks another's progress.
+    pub fn execute(&self, concurrency: usize, dest_dir: PathBuf) -> impl Stream<Item = Result<DownloadedFile>> + '_ {
+        let concurrency = concurrency.max(1);
+        let semaphore = Arc::new(Semaphore::new(concurrency));
+        let jobs = flatten_jobs(&self.urls);
+
+        stream::iter(jobs)
+            .map(move |(symbol, url)| {
+                let semaphore = semaphore.clone();
+                let client = self.client.clone();
+                let dest_dir = dest_dir.clone();
+                async move {
+                    let _permit = semaphore.acquire_owned().await.context("download semaphore closed")?;
+                    download_resumable(&client, symbol, url, &dest_dir).await
+                }
+            })
+            .buffer_unordered(concurrency)
+    }
+}
+
+/// Flatten a `symbol -> urls` map into `(symbol, url)` jobs, sorted by symbol
+/// so progress reporting (and tests) see a stable order.
+fn flatten_jobs(urls: &HashMap<String, Vec<String>>) -> Vec<(String, String)> {
+    let mut symbols: Vec<&String> = urls.keys().collect();
+    symbols.sort();
+    symbols
+        .into_iter()
+        .flat_map(|symbol| urls[symbol].iter().map(move |url| (symbol.clone(), url.clone())))
+        .collect()
+}
+
+async fn download_resumable(client: &Client, symbol: String, url: String, dest_dir: &Path) -> Result<DownloadedFile> {
+    let file_name = crate::extract_zip_name(&url).with_context(|| format!("no file name in {}", url))?;
+    let dest = dest_dir.join(&file_name);
+    let part_path = PathBuf::from(format!("{}.part", dest.display()));
+    let (max_retries, base_delay) = crate::retry_config();
+
+    for attempt in 0..=max_retries {
+        if attempt > 0 {
+            tokio::time::sleep(base_delay * 2u32.pow((attempt - 1) as u32)).await;
+        }
+        let existing_len = tokio::fs::metadata(&part_path).await.map(|m| m.len()).unwrap_or(0);
+        let mut request = client.get(&url);
+        if existing_len > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", existing_len));
+        }
+
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(err) if attempt < max_retries => {
+                println!("download attempt {} for {} failed: {}, retrying", attempt + 1, url, err);
+                continue;
+            }
+            Err(err) => return Err(err).context("download failed after exhausting retries"),
+        };
+
+        let status = response.status();
+        if !status.is_success() {
+            if status.is_server_error() && attempt < max_retries {
+                println!("download attempt {} for {} returned {}, retrying", attempt + 1, url, status);
+                continue;
+            }
+            anyhow::bail!("download of {} failed with status {}", url, status);
+        }
+
+        let resumed = status == reqwest::StatusCode::PARTIAL_CONTENT;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(resumed)
+            .truncate(!resumed)
+            .open(&part_path)
+            .await
+            .with_context(|| format!("open {}", part_path.display()))?;
+
+        let mut written = if resumed { existing_len } else { 0 };
+        let mut body = response.bytes_stream();
+        let stream_result: Result<()> = loop {
+            match body.next().await {
+                None => break Ok(()),
+                Some(Ok(chunk)) => {
+                    if let Err(err) = file.write_all(&chunk).await.context("write chunk to .part file") {
+                        break Err(err);
+                    }
+                    written += chunk.len() as u64;
+                }
+                Some(Err(err)) => break Err(err).context("read response chunk"),
+            }
+        };
+        file.flush().await.context("flush .part file")?;
+        drop(file);
+
+        if let Err(err) = stream_result {
+            if attempt < max_retries {
+                println!("download attempt {} for {} dropped mid-transfer: {}, retrying", attempt + 1, url, err);
+                continue;
+            }
+            return Err(err).context("download interrupted after exhausting retries");
+        }
+
+        tokio::fs::rename(&part_path, &dest)
+            .await
+            .with_context(|| format!("rename {} to {}", part_path.display(), dest.display()))?;
+
+        return Ok(DownloadedFile {
+            symbol,
+            url,
+            path: dest,
+            bytes: written,
+        });
+    }
+    unreachable!("loop always returns or bails within max_retries + 1 attempts")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flattens_jobs_in_stable_symbol_order() {
+        let mut urls = HashMap::new();
+        urls.insert("ETHUSDT".to_string(), vec!["https://x/ETHUSDT-1.zip".to_string()]);
+        urls.insert(
+            "BTCUSDT".to_string(),
+            vec![
+                "https://x/BTCUSDT-1.zip".to_string(),
+                "https://x/BTCUSDT-2.zip".to_string(),
+            ],
+        );
+
+        let jobs = flatten_jobs(&urls);
+        assert_eq!(
+            jobs,
+            vec![
+                ("BTCUSDT".to_string(), "https://x/BTCUSDT-1.zip".to_string()),
+                ("BTCUSDT".to_string(), "https://x/BTCUSDT-2.zip".to_string()),
+                ("ETHUSDT".to_string(), "https://x/ETHUSDT-1.zip".to_string()),
+            ]
+        );
+    }
+}
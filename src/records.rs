@@ -0,0 +1,186 @@
+//! Typed CSV records on top of the URL builder: deserialize a downloaded
+//! archive's CSV payload directly into [`Kline`]/[`Trade`]/[`AggTrade`]
+//! instead of routing it through the Polars/Parquet pipeline, for callers
+//! who just want `Vec<T>`.
+
+use crate::{download_to_file, has_header, raw_download_path, with_single_csv_entry, BinanceVisionClient};
+use anyhow::{Context, Result};
+use csv::ReaderBuilder;
+use serde::{Deserialize, Deserializer};
+use std::fs;
+use std::io::Read;
+
+/// Which Binance archive family a [`BinanceRecord`] deserializes rows from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataKind {
+    Klines,
+    Trades,
+    AggTrades,
+}
+
+impl DataKind {
+    /// The daily spot archive pattern (with a `SYMBOL` placeholder) Binance
+    /// publishes this kind of data under.
+    fn default_pattern(self) -> &'static str {
+        match self {
+            DataKind::Klines => "data/spot/daily/klines/SYMBOL/1m/",
+            DataKind::Trades => "data/spot/daily/trades/SYMBOL/",
+            DataKind::AggTrades => "data/spot/daily/aggTrades/SYMBOL/",
+        }
+    }
+}
+
+/// A typed CSV row deserializable from one of Binance's archive families.
+/// `kind()` tells [`get`] which endpoint pattern to list/download from.
+pub trait BinanceRecord: for<'de> Deserialize<'de> {
+    fn kind() -> DataKind;
+}
+
+/// One row of a `klines` archive. Binance's CSV carries a trailing `ignore`
+/// column (unused, reserved) after `taker_buy_quote`; it's kept here so
+/// positional (headerless) deserialization stays aligned with the real file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Kline {
+    pub open_time: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    pub close_time: i64,
+    pub quote_volume: f64,
+    pub trades: i64,
+    pub taker_buy_base: f64,
+    pub taker_buy_quote: f64,
+    pub ignore: i64,
+}
+
+impl BinanceRecord for Kline {
+    fn kind() -> DataKind {
+        DataKind::Klines
+    }
+}
+
+/// One row of a `trades` archive.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Trade {
+    pub trade_id: i64,
+    pub price: f64,
+    pub quantity: f64,
+    pub quote_quantity: f64,
+    pub time: i64,
+    #[serde(deserialize_with = "deserialize_flexible_bool")]
+    pub is_buyer_maker: bool,
+    #[serde(deserialize_with = "deserialize_flexible_bool")]
+    pub is_best_match: bool,
+}
+
+impl BinanceRecord for Trade {
+    fn kind() -> DataKind {
+        DataKind::Trades
+    }
+}
+
+/// One row of an `aggTrades` archive.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AggTrade {
+    pub agg_trade_id: i64,
+    pub price: f64,
+    pub quantity: f64,
+    pub first_trade_id: i64,
+    pub last_trade_id: i64,
+    pub transact_time: i64,
+    #[serde(deserialize_with = "deserialize_flexible_bool")]
+    pub is_buyer_maker: bool,
+}
+
+impl BinanceRecord for AggTrade {
+    fn kind() -> DataKind {
+        DataKind::AggTrades
+    }
+}
+
+/// Binance writes CSV booleans as `True`/`False`; accept that alongside the
+/// lowercase form serde's own `bool` deserializer expects.
+fn deserialize_flexible_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw = String::deserialize(deserializer)?;
+    match raw.to_lowercase().as_str() {
+        "true" | "1" => Ok(true),
+        "false" | "0" => Ok(false),
+        other => Err(serde::de::Error::custom(format!("invalid boolean: {}", other))),
+    }
+}
+
+/// Download `symbol`'s daily archive for `date` (`YYYY-MM-DD`) and
+/// deserialize its rows into `Vec<T>`, inferring which Binance endpoint to
+/// list/download from via `T::kind()`.
+pub fn get<T: BinanceRecord>(client: &BinanceVisionClient, symbol: &str, date: &str) -> Result<Vec<T>> {
+    let pattern = T::kind().default_pattern();
+    let urls = client.list_files(pattern, symbol)?;
+    let url = urls
+        .into_iter()
+        .find(|url| url.contains(date))
+        .with_context(|| format!("no {:?} archive for {} on {}", T::kind(), symbol, date))?;
+
+    let dest = raw_download_path(pattern, &url)?;
+    download_to_file(&client.download_client, &url, &dest)?;
+    let records = parse_archive(&dest);
+    let _ = fs::remove_file(&dest);
+    records
+}
+
+fn parse_archive<T: BinanceRecord>(zip_path: &std::path::Path) -> Result<Vec<T>> {
+    let archive_bytes = fs::read(zip_path).with_context(|| format!("open {}", zip_path.display()))?;
+    with_single_csv_entry(&archive_bytes, |_name, zipped| {
+        let mut csv_content = String::new();
+        zipped.read_to_string(&mut csv_content)?;
+
+        let mut reader = ReaderBuilder::new()
+            .has_headers(has_header(&csv_content))
+            .from_reader(csv_content.as_bytes());
+        let mut records = Vec::new();
+        for result in reader.deserialize() {
+            records.push(result.context("deserialize csv row")?);
+        }
+        Ok(records)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_klines_without_header() {
+        let csv_content = "1,2,3,4,5,6,7,8,9,10,11,0\n";
+        let mut reader = ReaderBuilder::new()
+            .has_headers(has_header(csv_content))
+            .from_reader(csv_content.as_bytes());
+        let records: Vec<Kline> = reader.deserialize().collect::<Result<_, _>>().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].open_time, 1);
+        assert_eq!(records[0].close, 5.0);
+    }
+
+    #[test]
+    fn parses_flexible_booleans() {
+        let csv_content = "1,2.0,3.0,4.0,5,True,False\n";
+        let mut reader = ReaderBuilder::new()
+            .has_headers(has_header(csv_content))
+            .from_reader(csv_content.as_bytes());
+        let records: Vec<Trade> = reader.deserialize().collect::<Result<_, _>>().unwrap();
+        assert_eq!(records.len(), 1);
+        assert!(records[0].is_buyer_maker);
+        assert!(!records[0].is_best_match);
+    }
+
+    #[test]
+    fn reports_data_kind_per_record_type() {
+        assert_eq!(Kline::kind(), DataKind::Klines);
+        assert_eq!(Trade::kind(), DataKind::Trades);
+        assert_eq!(AggTrade::kind(), DataKind::AggTrades);
+    }
+}
@@ -0,0 +1,366 @@
+//! `serve` subcommand: a small HTTP server rooted at [`crate::CLEAN_ROOT`] that
+//! browses the Hive-partitioned Parquet dataset and answers time-range
+//! queries over it, reusing [`crate::query_range`]'s scan/filter path.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use polars::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// Bind `addr` (e.g. `"127.0.0.1:8080"`) and serve [`crate::CLEAN_ROOT`] until
+/// the process is killed, one thread per connection.
+pub fn serve(addr: &str) -> Result<()> {
+    let listener = TcpListener::bind(addr).with_context(|| format!("bind {}", addr))?;
+    println!("Serving {} on http://{}", crate::CLEAN_ROOT, addr);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                println!("Connection failed: {}", err);
+                continue;
+            }
+        };
+        thread::spawn(move || {
+            if let Err(err) = handle_connection(stream) {
+                println!("Request failed: {}", err);
+            }
+        });
+    }
+    Ok(())
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    headers: HashMap<String, String>,
+}
+
+fn handle_connection(mut stream: TcpStream) -> Result<()> {
+    let request = read_request(&mut stream)?;
+    if request.method != "GET" {
+        return write_response(&mut stream, 405, "text/plain", b"method not allowed".to_vec(), None);
+    }
+
+    if request.path == "/query" {
+        return respond_query(&mut stream, &request);
+    }
+    respond_dataset_path(&mut stream, &request)
+}
+
+/// Read the request line and headers (no body is expected for `GET`).
+fn read_request(stream: &mut TcpStream) -> Result<Request> {
+    let mut raw = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        let read = stream.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        raw.extend_from_slice(&buf[..read]);
+        if raw.windows(4).any(|w| w == b"\r\n\r\n") {
+            break;
+        }
+    }
+    let text = String::from_utf8_lossy(&raw);
+    let mut lines = text.lines();
+    let request_line = lines.next().context("empty request")?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().context("missing method")?.to_string();
+    let target = parts.next().context("missing path")?.to_string();
+
+    let mut headers = HashMap::new();
+    for line in lines {
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let (path, query_string) = target.split_once('?').unwrap_or((target.as_str(), ""));
+    Ok(Request {
+        method,
+        path: urlencoding::decode(path).map(|s| s.into_owned()).unwrap_or(path.to_string()),
+        query: parse_query(query_string),
+        headers,
+    })
+}
+
+fn parse_query(query_string: &str) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+    for pair in query_string.split('&').filter(|s| !s.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let key = urlencoding::decode(key).map(|s| s.into_owned()).unwrap_or(key.to_string());
+        let value = urlencoding::decode(value).map(|s| s.into_owned()).unwrap_or(value.to_string());
+        params.insert(key, value);
+    }
+    params
+}
+
+/// Resolve `request.path` to a location under `CLEAN_ROOT`, rejecting `..`
+/// segments so a client can't escape the dataset root.
+fn resolve_dataset_path(path: &str) -> Result<PathBuf> {
+    let relative = path.trim_start_matches('/');
+    anyhow::ensure!(
+        !relative.split('/').any(|segment| segment == ".."),
+        "path traversal rejected"
+    );
+    Ok(PathBuf::from(crate::CLEAN_ROOT).join(relative))
+}
+
+fn respond_dataset_path(stream: &mut TcpStream, request: &Request) -> Result<()> {
+    let fs_path = match resolve_dataset_path(&request.path) {
+        Ok(path) => path,
+        Err(_) => return write_response(stream, 400, "text/plain", b"invalid path".to_vec(), None),
+    };
+
+    if fs_path.is_dir() {
+        return respond_directory_listing(stream, request, &fs_path);
+    }
+    if fs_path.is_file() {
+        return respond_file(stream, request, &fs_path);
+    }
+    write_response(stream, 404, "text/plain", b"not found".to_vec(), None)
+}
+
+fn respond_directory_listing(stream: &mut TcpStream, request: &Request, dir: &Path) -> Result<()> {
+    let mut entries: Vec<(String, bool)> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| {
+            let is_dir = entry.path().is_dir();
+            (entry.file_name().to_string_lossy().into_owned(), is_dir)
+        })
+        .collect();
+    entries.sort();
+
+    let as_json = request.query.get("format").map(String::as_str) == Some("json")
+        || request.headers.get("accept").map(|a| a.contains("application/json")).unwrap_or(false);
+
+    if as_json {
+        let items: Vec<String> = entries
+            .iter()
+            .map(|(name, is_dir)| format!("{{\"name\":{:?},\"is_dir\":{}}}", name, is_dir))
+            .collect();
+        let body = format!("[{}]", items.join(","));
+        return write_response(stream, 200, "application/json", body.into_bytes(), None);
+    }
+
+    let links: Vec<String> = entries
+        .iter()
+        .map(|(name, is_dir)| {
+            let suffix = if *is_dir { "/" } else { "" };
+            format!("<li><a href=\"{name}{suffix}\">{name}{suffix}</a></li>")
+        })
+        .collect();
+    let body = format!(
+        "<html><body><h1>{}</h1><ul>{}</ul></body></html>",
+        request.path,
+        links.join("")
+    );
+    write_response(stream, 200, "text/html", body.into_bytes(), None)
+}
+
+fn content_type_for(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("parquet") => "application/octet-stream",
+        Some("csv") => "text/csv",
+        Some("json") => "application/json",
+        Some("txt") => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Serve `path`'s bytes, honoring a `Range: bytes=start-end` request header.
+fn respond_file(stream: &mut TcpStream, request: &Request, path: &Path) -> Result<()> {
+    let content_type = content_type_for(path);
+    let mut file = fs::File::open(path)?;
+    let total_len = file.metadata()?.len();
+
+    if let Some(range) = request.headers.get("range") {
+        if let Some((start, end)) = parse_range(range, total_len) {
+            let len = end - start + 1;
+            file.seek(SeekFrom::Start(start))?;
+            let mut body = vec![0u8; len as usize];
+            file.read_exact(&mut body)?;
+            let content_range = format!("bytes {}-{}/{}", start, end, total_len);
+            return write_response(stream, 206, content_type, body, Some(content_range));
+        }
+    }
+
+    let mut body = Vec::with_capacity(total_len as usize);
+    file.read_to_end(&mut body)?;
+    write_response(stream, 200, content_type, body, None)
+}
+
+/// Parse a single-range `bytes=start-end` (or `bytes=start-`) header value.
+fn parse_range(header: &str, total_len: u64) -> Option<(u64, u64)> {
+    let spec = header.strip_prefix("bytes=")?;
+    let (start, end) = spec.split_once('-')?;
+    let start: u64 = start.parse().ok()?;
+    let end: u64 = if end.is_empty() {
+        total_len.saturating_sub(1)
+    } else {
+        end.parse().ok()?
+    };
+    if start > end || end >= total_len {
+        return None;
+    }
+    Some((start, end))
+}
+
+fn respond_query(stream: &mut TcpStream, request: &Request) -> Result<()> {
+    let pattern = match request.query.get("pattern") {
+        Some(pattern) => pattern,
+        None => return write_response(stream, 400, "text/plain", b"missing pattern".to_vec(), None),
+    };
+    let symbol = match request.query.get("symbol") {
+        Some(symbol) => symbol,
+        None => return write_response(stream, 400, "text/plain", b"missing symbol".to_vec(), None),
+    };
+    let from = match request.query.get("from").and_then(|v| parse_time(v)) {
+        Some(from) => from,
+        None => return write_response(stream, 400, "text/plain", b"missing or invalid from".to_vec(), None),
+    };
+    let to = match request.query.get("to").and_then(|v| parse_time(v)) {
+        Some(to) => to,
+        None => return write_response(stream, 400, "text/plain", b"missing or invalid to".to_vec(), None),
+    };
+    let format = request.query.get("format").map(String::as_str).unwrap_or("csv");
+
+    let mut df = match crate::query_range(pattern, symbol, from, to) {
+        Ok(df) => df,
+        Err(err) => return write_response(stream, 404, "text/plain", err.to_string().into_bytes(), None),
+    };
+
+    match format {
+        "json" => {
+            let body = dataframe_to_json(&df)?;
+            write_response(stream, 200, "application/json", body.into_bytes(), None)
+        }
+        _ => {
+            let mut buffer = Vec::new();
+            CsvWriter::new(&mut buffer).finish(&mut df)?;
+            write_response(stream, 200, "text/csv", buffer, None)
+        }
+    }
+}
+
+/// Parse an RFC3339 timestamp, falling back to an epoch-millisecond integer.
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(value) {
+        return Some(parsed.with_timezone(&Utc));
+    }
+    value.parse::<i64>().ok().and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+}
+
+fn anyvalue_to_json(value: AnyValue) -> String {
+    match value {
+        AnyValue::Null => "null".to_string(),
+        AnyValue::Boolean(b) => b.to_string(),
+        AnyValue::Utf8(s) => format!("{:?}", s),
+        AnyValue::Int8(n) => n.to_string(),
+        AnyValue::Int16(n) => n.to_string(),
+        AnyValue::Int32(n) => n.to_string(),
+        AnyValue::Int64(n) => n.to_string(),
+        AnyValue::UInt8(n) => n.to_string(),
+        AnyValue::UInt16(n) => n.to_string(),
+        AnyValue::UInt32(n) => n.to_string(),
+        AnyValue::UInt64(n) => n.to_string(),
+        AnyValue::Float32(n) => n.to_string(),
+        AnyValue::Float64(n) => n.to_string(),
+        other => format!("{:?}", format!("{:?}", other)),
+    }
+}
+
+fn dataframe_to_json(df: &DataFrame) -> Result<String> {
+    let columns = df.get_column_names();
+    let mut rows = Vec::with_capacity(df.height());
+    for row_idx in 0..df.height() {
+        let mut fields = Vec::with_capacity(columns.len());
+        for name in &columns {
+            let value = df.column(name)?.get(row_idx)?;
+            fields.push(format!("{:?}:{}", name, anyvalue_to_json(value)));
+        }
+        rows.push(format!("{{{}}}", fields.join(",")));
+    }
+    Ok(format!("[{}]", rows.join(",")))
+}
+
+fn write_response(
+    stream: &mut TcpStream,
+    status: u16,
+    content_type: &str,
+    body: Vec<u8>,
+    content_range: Option<String>,
+) -> Result<()> {
+    let reason = match status {
+        200 => "OK",
+        206 => "Partial Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Error",
+    };
+    let mut response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nAccept-Ranges: bytes\r\n",
+        status,
+        reason,
+        content_type,
+        body.len()
+    );
+    if let Some(content_range) = content_range {
+        response.push_str(&format!("Content-Range: {}\r\n", content_range));
+    }
+    response.push_str("\r\n");
+    stream.write_all(response.as_bytes())?;
+    stream.write_all(&body)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_full_byte_range() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some((0, 99)));
+    }
+
+    #[test]
+    fn parses_open_ended_byte_range() {
+        assert_eq!(parse_range("bytes=500-", 1000), Some((500, 999)));
+    }
+
+    #[test]
+    fn rejects_out_of_bounds_range() {
+        assert_eq!(parse_range("bytes=0-1000", 1000), None);
+    }
+
+    #[test]
+    fn rejects_path_traversal() {
+        assert!(resolve_dataset_path("/../secrets").is_err());
+        assert!(resolve_dataset_path("/pattern/../../secrets").is_err());
+    }
+
+    #[test]
+    fn resolves_plain_dataset_path() {
+        let resolved = resolve_dataset_path("/data/spot/daily/klines").unwrap();
+        assert_eq!(resolved, PathBuf::from(crate::CLEAN_ROOT).join("data/spot/daily/klines"));
+    }
+
+    #[test]
+    fn parses_rfc3339_and_epoch_millis_times() {
+        let from_rfc3339 = parse_time("2024-01-01T00:00:00Z").unwrap();
+        assert_eq!(from_rfc3339.timestamp_millis(), 1704067200000);
+        let from_millis = parse_time("1704067200000").unwrap();
+        assert_eq!(from_millis.timestamp_millis(), 1704067200000);
+        assert!(parse_time("not-a-time").is_none());
+    }
+}